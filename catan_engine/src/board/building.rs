@@ -16,8 +16,8 @@ impl Building {
         }
     }
 
-    pub fn from_string(c: &String) -> Building {
-        match c.as_str() {
+    pub fn from_string(c: &str) -> Building {
+        match c {
             "S" => Building::Settlement,
             "C" => Building::City,
             _ => unimplemented!("Invalid building type"),
@@ -1,71 +1,101 @@
 use core::panic;
+use std::fmt::Display;
 
-use super::{Board, I8_R};
+use super::{Board, Layout};
 
 pub const X_EDGE: i8 = 0;
 pub const Y_EDGE: i8 = 1;
 pub const Z_EDGE: i8 = 2;
 
-impl Board {
-    
+impl Layout {
+
     /**
-     * Get a list of the valid tile coordinates
-     * 
-     * TODO - this can be computed once for efficiency, it will never change for boards of radius R
+     * Check if a tile coordinate is in the layout
      */
-    pub fn get_tile_coords() -> Vec<Coordinate2> {
-        let mut vec = Vec::new();
-        for y in -I8_R ..= I8_R {
-            for x in -I8_R ..= I8_R {
-                if (-x -y).abs() <= I8_R {
-                    vec.push(Coordinate2::new(x, y));
-                }
-            }
+    pub fn is_valid_tile(&self, coord: &Coordinate2) -> bool {
+        let r = self.get_radius();
+        coord.is_valid_tile(r) && match self {
+            Layout::Hexagon(_) => true,
+            // The last tile of every row is removed from the hexagon
+            Layout::Extension => coord.x != r && coord.x + coord.y != r,
         }
-        vec
     }
 
     /**
-     * Get a list of the valid vertex coordinates
-     * 
-     * TODO - this can be computed once for efficiency, it will never change for boards of radius R
+     * Check if a vertex coordinate is in the layout, it must touch one of the tiles
      */
-    pub fn get_vertex_coords() -> Vec<Coordinate3> {
-        let mut vec: Vec<Coordinate3> = Vec::new();
-        for x in -I8_R - 1 ..= I8_R + 1 {
-            for y in -I8_R - 1 ..= I8_R + 1 {
-                for z in -I8_R - 1 ..= I8_R + 1 {
-                    let coord = Coordinate3::new(x, y, z);
-                    if coord.sum().abs() == 1 && coord.abs_sum() <= I8_R * 2 + 1 {
-                        vec.push(coord);
-                    }
-                }
-            }
+    pub fn is_valid_vertex(&self, coord: &VertexCoord) -> bool {
+        coord.is_valid_vertex(self.get_radius()) && match self {
+            Layout::Hexagon(_) => true,
+            Layout::Extension => Board::get_vertex_adjacent_tile_coords(coord).iter().any(|e| self.is_valid_tile(e)),
         }
-        vec
     }
 
     /**
-     * Get a list of the valid edge coordinates
+     * Check if an edge coordinate is in the layout, both of its vertices must be in the layout
      */
-    pub fn get_edge_coords() -> Vec<Coordinate3> {
-        let mut vec: Vec<Coordinate3> = Vec::new();
-        for x in -I8_R - 1 ..= I8_R {
-            for y in (-I8_R ..= I8_R + 1).rev() {
-                if Coordinate2::new(x, y).is_valid_tile() || Coordinate2::new(x + 1, y + 1).is_valid_tile() {
-                    for d in 0 ..= 2 {
-                        vec.push(Coordinate3::new(d, x, y));
-                    }
-                }
-            }
-        }
-        vec
+    pub fn is_valid_edge(&self, coord: &EdgeCoord) -> bool {
+        coord.is_valid_edge(self.get_radius())
+            && Board::get_edge_adjacent_vertex_coords(coord).iter().all(|e| self.is_valid_vertex(e))
+    }
+
+}
+
+impl Board {
+
+    /**
+     * Check if a tile coordinate is on this board
+     */
+    pub fn is_valid_tile(&self, coord: &Coordinate2) -> bool {
+        self.topology.get_tile_id(coord).is_some()
+    }
+
+    /**
+     * Check if a vertex coordinate is on this board, it must touch one of the tiles
+     */
+    pub fn is_valid_vertex(&self, coord: &VertexCoord) -> bool {
+        self.topology.get_vertex_id(coord).is_some()
+    }
+
+    /**
+     * Check if an edge coordinate is on this board, both of its vertices must be on the board
+     */
+    pub fn is_valid_edge(&self, coord: &EdgeCoord) -> bool {
+        self.topology.get_edge_id(coord).is_some()
+    }
+    
+    /**
+     * Get a list of the valid tile coordinates, in the order of their ids
+     */
+    pub fn get_tile_coords(&self) -> &'static [Coordinate2] {
+        self.topology.get_tiles()
+    }
+
+    /**
+     * Get a list of the valid vertex coordinates, in the order of their ids
+     */
+    pub fn get_vertex_coords(&self) -> &'static [VertexCoord] {
+        self.topology.get_vertices()
+    }
+
+    /**
+     * Get a list of the valid edge coordinates, in the order of their ids
+     */
+    pub fn get_edge_coords(&self) -> &'static [EdgeCoord] {
+        self.topology.get_edges()
+    }
+
+    /**
+     * Get the edges on the coast of the board, touching exactly one tile
+     */
+    pub fn get_coast_edge_coords(&self) -> Vec<EdgeCoord> {
+        self.topology.get_coast_edges().iter().map(|e| self.topology.get_edges()[*e]).collect()
     }
 
     /**
      * Get the edge coordinate between 2 vertices.
      */
-    pub fn get_edge_between_vertices_coords(a: &Coordinate3, b: &Coordinate3) -> Coordinate3 {
+    pub fn get_edge_between_vertices_coords(a: &VertexCoord, b: &VertexCoord) -> EdgeCoord {
         let direction = if a.x == b.x {
             X_EDGE
         } else if a.y == b.y {
@@ -77,7 +107,7 @@ impl Board {
         };
 
         // Determine which point is prime and which is not
-        let (p, pp) = if a.sum() < 0 {
+        let (p, pp) = if a.is_prime() {
             (b, a)
         } else {
             (a, b)
@@ -90,7 +120,7 @@ impl Board {
             _ => unreachable!()
         };
 
-        Coordinate3::new(direction, x, y)
+        EdgeCoord::new(direction, x, y)
     }
 
     /**
@@ -98,12 +128,12 @@ impl Board {
      * 
      * Note: if a prime vertex is passed, the regular vertex pair is returned
      */
-    pub fn get_prime_pair_vertex(direction: i8, coord: &Coordinate3) -> Coordinate3 {
-        let factor = if coord.sum() < 0 { -1 } else { 1 };
+    pub fn get_prime_pair_vertex(direction: i8, coord: &VertexCoord) -> VertexCoord {
+        let factor = if coord.is_prime() { -1 } else { 1 };
         match direction {
-            X_EDGE => Coordinate3::new(coord.x, coord.y - factor, coord.z - factor),
-            Y_EDGE => Coordinate3::new(coord.x - factor, coord.y , coord.z - factor),
-            Z_EDGE => Coordinate3::new(coord.x - factor, coord.y - factor, coord.z),
+            X_EDGE => VertexCoord::new(coord.x, coord.y - factor, coord.z - factor),
+            Y_EDGE => VertexCoord::new(coord.x - factor, coord.y , coord.z - factor),
+            Z_EDGE => VertexCoord::new(coord.x - factor, coord.y - factor, coord.z),
             _ => unreachable!()
         }
     }
@@ -111,12 +141,12 @@ impl Board {
     /**
      * Providing a vertex coordinate, get all the surrounding edge coordinates
      */
-    pub fn get_vertex_adjacent_edge_coords(coord: &Coordinate3) -> Vec<Coordinate3> {
+    pub fn get_vertex_adjacent_edge_coords(&self, coord: &VertexCoord) -> Vec<EdgeCoord> {
         let mut vec =  Vec::new();
         for direction in 0 .. 3 {
             let pair = Self::get_prime_pair_vertex(direction, coord);
-            if pair.abs_sum() <= 2 * I8_R + 1 {
-                let result: Coordinate3 = Self::get_edge_between_vertices_coords(coord, &pair);
+            if self.is_valid_vertex(&pair) {
+                let result: EdgeCoord = Self::get_edge_between_vertices_coords(coord, &pair);
                 vec.push(result);
             }
         }
@@ -126,36 +156,34 @@ impl Board {
     /**
      * Get the tiles coordinates adjacent to a vertex coordinate
      */
-    pub fn get_vertex_adjacent_tile_coords(coord: &Coordinate3) -> Vec<Coordinate2> {
-        if coord.sum() == -1 {
+    pub fn get_vertex_adjacent_tile_coords(coord: &VertexCoord) -> Vec<Coordinate2> {
+        if coord.is_prime() {
             vec![
                 Coordinate2::new(coord.x, coord.y),
                 Coordinate2::new(coord.x, coord.y + 1),
                 Coordinate2::new(coord.x + 1, coord.y),
             ] 
-        } else if coord.sum() == 1 {
+        } else {
             vec![
                 Coordinate2::new(coord.x, coord.y - 1),
                 Coordinate2::new(coord.x - 1, coord.y),
                 Coordinate2::new(coord.x, coord.y),
             ] 
-        } else {
-            panic!("Invalid vertex coordinate provided");
         }
     }
 
     /**
      * Get the 6 vertices adjacent to a tile
      */
-    pub fn get_tile_adjacent_vertex_coords(coord: &Coordinate2) -> [Coordinate3; 6] {
+    pub fn get_tile_adjacent_vertex_coords(coord: &Coordinate2) -> [VertexCoord; 6] {
         let z = -coord.x - coord.y;
         [
-            Coordinate3::new(coord.x - 1, coord.y, z),
-            Coordinate3::new(coord.x + 1, coord.y, z),
-            Coordinate3::new(coord.x, coord.y - 1, z),
-            Coordinate3::new(coord.x, coord.y + 1, z),
-            Coordinate3::new(coord.x, coord.y, z - 1),
-            Coordinate3::new(coord.x, coord.y, z + 1),
+            VertexCoord::new(coord.x - 1, coord.y, z),
+            VertexCoord::new(coord.x + 1, coord.y, z),
+            VertexCoord::new(coord.x, coord.y - 1, z),
+            VertexCoord::new(coord.x, coord.y + 1, z),
+            VertexCoord::new(coord.x, coord.y, z - 1),
+            VertexCoord::new(coord.x, coord.y, z + 1),
         ]
     }
 
@@ -163,43 +191,54 @@ impl Board {
     /**
      * Providing an edge coordinate, get all the surrounding vertices coordinates
      */
-    pub fn get_edge_adjacent_vertex_coords(coord: &Coordinate3) -> Vec<Coordinate3> {
+    pub fn get_edge_adjacent_vertex_coords(coord: &EdgeCoord) -> Vec<VertexCoord> {
 
         // X(-2, 2) -> (0, -1, 2)
         // x + y + z = 1
 
         // Get the x' and y value of the edge
-        let reg = match coord.x {
+        let reg = match coord.axis {
             // X edge
             0 => {
                 let y = coord.y + 1;
                 let z = coord.z;
                 let x = 1 - y - z;
-                Coordinate3::new(x, y, z)
+                VertexCoord::new(x, y, z)
             },
             // Y edge
             1 => {
                 let z = coord.y + 1;
                 let x = coord.z;
                 let y = 1 - x - z;
-                Coordinate3::new(x, y, z)
+                VertexCoord::new(x, y, z)
             },
             // Z edge
             2 => {
                 let x = coord.y + 1;
                 let y = coord.z;
                 let z = 1 - x - y;
-                Coordinate3::new(x, y, z)
+                VertexCoord::new(x, y, z)
             },
             _ => unreachable!(),
         };
 
 
-        let prime =  Self::get_prime_pair_vertex(coord.x, &reg);
+        let prime =  Self::get_prime_pair_vertex(coord.axis, &reg);
         vec![reg, prime]
 
     }
 
+    /**
+     * Get the 2 tiles coordinates on either side of an edge
+     */
+    pub fn get_edge_adjacent_tile_coords(coord: &EdgeCoord) -> Vec<Coordinate2> {
+        let vertices = Self::get_edge_adjacent_vertex_coords(coord);
+        let other = Self::get_vertex_adjacent_tile_coords(&vertices[1]);
+        Self::get_vertex_adjacent_tile_coords(&vertices[0]).into_iter().filter(|e| {
+            other.contains(e)
+        }).collect()
+    }
+
     /**
      * Utility function to get the vertex for radius r
      */
@@ -216,7 +255,7 @@ impl Board {
      * If a tile is on the edge of the board, those coordinates
      * are not considered.
      */
-    pub fn get_tile_neighbor_coords(coord: &Coordinate2) -> Vec<Coordinate2> {
+    pub fn get_tile_neighbor_coords(&self, coord: &Coordinate2) -> Vec<Coordinate2> {
         let mut vec = Vec::new();
         for dx in coord.x - 1 ..= coord.x + 1 {
             for dy in coord.y - 1 ..= coord.y + 1 {
@@ -232,14 +271,14 @@ impl Board {
 
         // Filter only valid coords
         vec.into_iter().filter(|e| {
-            e.is_valid_tile()
+            self.is_valid_tile(e)
         }).collect()
     }
 
     /**
      * Get the vertex coordinate between 3 tiles
      */
-    pub fn get_vertex_between_tiles_coord(q: Coordinate2, r: Coordinate2, s: Coordinate2) -> Coordinate3 {
+    pub fn get_vertex_between_tiles_coord(q: Coordinate2, r: Coordinate2, s: Coordinate2) -> VertexCoord {
         
         // Find A
         let (a, u1, u2) = if q.y == r.y {
@@ -262,30 +301,32 @@ impl Board {
             } else {
                 (u2, u1)
             };
-            Coordinate3::new(a.x, b.y, -c.x - c.y)
+            VertexCoord::try_new(a.x, b.y, -c.x - c.y)
         } else {
             let (b, c) = if a.x == u1.y {
                 (u2, u1)
             } else {
                 (u1, u2)
             };
-            Coordinate3::new(c.x, b.y, -a.x - a.y)
+            VertexCoord::try_new(c.x, b.y, -a.x - a.y)
         };
 
-        if result.sum().abs() != 1 {
-            panic!("Invalid tile coordinates provided");
+        match result {
+            Some(result) => result,
+            None => panic!("Invalid tile coordinates provided"),
         }
-
-        result
     }
 
 }
 
 
+/**
+ * Sums of the values of a coordinate. They are widened so coordinates far off the
+ * board do not overflow.
+ */
 pub trait VectorOps {
-    fn sum(&self) -> i8;
-    fn abs_max(&self) -> i8;
-    fn abs_sum(&self) -> i8;
+    fn sum(&self) -> i16;
+    fn abs_sum(&self) -> i16;
 }
 
 #[derive(Clone, Debug)]
@@ -300,28 +341,25 @@ impl Coordinate2 {
     }
 
     /**
-     * Check if a tile coordinate is valid for this board
+     * Check if a tile coordinate is valid for a board of radius r
      */
-    pub fn is_valid_tile(&self) -> bool {
-        if self.x.abs() > I8_R || self.y.abs() > I8_R || (-self.x - self.y).abs() > I8_R  {
+    pub fn is_valid_tile(&self, r: i8) -> bool {
+        let r = r as i16;
+        if (self.x as i16).abs() > r || (self.y as i16).abs() > r || self.sum().abs() > r {
             return false
         }
-        return true
+        true
     }
 
 }
 
 impl VectorOps for Coordinate2 {
-    fn sum(&self) -> i8 {
-        return self.x + self.y
+    fn sum(&self) -> i16 {
+        self.x as i16 + self.y as i16
     }
 
-    fn abs_max(&self) -> i8 {
-        i8::max(self.x.abs(), self.y.abs())
-    }
-
-    fn abs_sum(&self) -> i8 {
-        self.x.abs() + self.y.abs()
+    fn abs_sum(&self) -> i16 {
+        (self.x as i16).abs() + (self.y as i16).abs()
     }
 }
 
@@ -334,53 +372,143 @@ impl PartialEq for Coordinate2 {
     
 }
 
-#[derive(Debug)]
-pub struct Coordinate3 {
-    pub x: i8,
-    pub y: i8,
-    pub z: i8,
+/**
+ * The point between 3 tiles. The values always sum to 1, or to -1 for a "prime" vertex.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct VertexCoord {
+    pub(crate) x: i8,
+    pub(crate) y: i8,
+    pub(crate) z: i8,
 }
 
-impl Coordinate3 {
+impl VertexCoord {
+
+    /**
+     * Create a vertex coordinate, the values must sum to 1 or -1
+     */
     pub fn new(x: i8, y: i8, z: i8) -> Self {
-        Coordinate3 { x, y, z }
+        match Self::try_new(x, y, z) {
+            Some(coord) => coord,
+            None => panic!("Invalid vertex coordinate: ({}, {}, {})", x, y, z),
+        }
+    }
+
+    /**
+     * Create a vertex coordinate, or None if the values do not sum to 1 or -1
+     */
+    pub fn try_new(x: i8, y: i8, z: i8) -> Option<Self> {
+        if (x as i16 + y as i16 + z as i16).abs() != 1 {
+            return None
+        }
+        Some(VertexCoord { x, y, z })
+    }
+
+    pub fn get_x(&self) -> i8 {
+        self.x
+    }
+
+    pub fn get_y(&self) -> i8 {
+        self.y
+    }
+
+    pub fn get_z(&self) -> i8 {
+        self.z
     }
 
     /**
-     * Utility to determine if a Vertex is on the game board
+     * Check if the vertex has the "prime" shape, summing to -1
      */
-    pub fn is_valid_vertex(&self) -> bool {
-        self.abs_sum() <= 2 * I8_R + 1
+    pub fn is_prime(&self) -> bool {
+        self.sum() < 0
     }
 
     /**
-     * Utility to determine if an Edge coordinate is on the game board
+     * Utility to determine if a Vertex is on a game board of radius r
      */
-    pub fn is_valid_edge(&self) -> bool {
-        self.x >= 0 && self.x <= 2
-            && self.y >= -I8_R && self.y <= I8_R + 1
-            && self.z >= -I8_R && self.z <= I8_R + 1
+    pub fn is_valid_vertex(&self, r: i8) -> bool {
+        self.abs_sum() <= 2 * r as i16 + 1
     }
+
 }
 
-impl VectorOps for Coordinate3 {
-    fn sum(&self) -> i8 {
-        return self.x + self.y + self.z
+impl VectorOps for VertexCoord {
+    fn sum(&self) -> i16 {
+        self.x as i16 + self.y as i16 + self.z as i16
     }
 
-    fn abs_max(&self) -> i8 {
-        i8::max(i8::max(self.x.abs(), self.y.abs()), self.y.abs())
+    fn abs_sum(&self) -> i16 {
+        (self.x as i16).abs() + (self.y as i16).abs() + (self.z as i16).abs()
     }
-    
-    fn abs_sum(&self) -> i8 {
-        self.x.abs() + self.y.abs() + self.z.abs()
+}
+
+/**
+ * An edge between 2 vertices, given by the axis it is perpendicular to and the (y, z)
+ * values of the edge on that axis
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct EdgeCoord {
+    pub(crate) axis: i8,
+    pub(crate) y: i8,
+    pub(crate) z: i8,
+}
+
+impl EdgeCoord {
+
+    /**
+     * Create an edge coordinate, the axis must be X_EDGE, Y_EDGE or Z_EDGE
+     */
+    pub fn new(axis: i8, y: i8, z: i8) -> Self {
+        match Self::try_new(axis, y, z) {
+            Some(coord) => coord,
+            None => panic!("Invalid edge axis: {}", axis),
+        }
     }
+
+    /**
+     * Create an edge coordinate, or None if the axis is not X_EDGE, Y_EDGE or Z_EDGE
+     */
+    pub fn try_new(axis: i8, y: i8, z: i8) -> Option<Self> {
+        if !(X_EDGE ..= Z_EDGE).contains(&axis) {
+            return None
+        }
+        Some(EdgeCoord { axis, y, z })
+    }
+
+    pub fn get_axis(&self) -> i8 {
+        self.axis
+    }
+
+    pub fn get_y(&self) -> i8 {
+        self.y
+    }
+
+    pub fn get_z(&self) -> i8 {
+        self.z
+    }
+
+    /**
+     * Utility to determine if an Edge coordinate is on a game board of radius r,
+     * both of its vertices must be on the board
+     */
+    pub fn is_valid_edge(&self, r: i8) -> bool {
+        let bound = r as i16 + 1;
+        (self.y as i16).abs() <= bound && (self.z as i16).abs() <= bound
+            && Board::get_edge_adjacent_vertex_coords(self).iter().all(|e| e.is_valid_vertex(r))
+    }
+
 }
 
-impl PartialEq for Coordinate3 {
+/**
+ * A coordinate that could not be read from notation
+ */
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ParseCoordinateError(pub String);
 
-    fn eq(&self, other: &Self) -> bool {
-        self.x == other.x && self.y == other.y && self.z == other.z
+impl Display for ParseCoordinateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid coordinate notation: {}", self.0)
     }
+}
 
-}
\ No newline at end of file
+impl std::error::Error for ParseCoordinateError {}
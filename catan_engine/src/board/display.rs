@@ -1,7 +1,7 @@
 use std::fmt::Display;
 use colored::{Color, Colorize};
-use crate::board::{building::Building, coordinate::{Coordinate2, X_EDGE, Y_EDGE, Z_EDGE}, tile::Tile, I8_R, PORT_LOCATIONS, USIZE_R};
-use super::{coordinate::Coordinate3, Board};
+use crate::board::{building::Building, coordinate::{Coordinate2, EdgeCoord, VertexCoord, X_EDGE, Y_EDGE, Z_EDGE}, tile::Tile};
+use super::Board;
 
 impl Board {
     
@@ -17,8 +17,9 @@ impl Board {
             } 
         }
 
+        let r = self.get_radius();
         println!();
-        for y in -I8_R ..= I8_R {
+        for y in -r ..= r {
 
             // Print Z and X axis roads
             if y <= 0 {
@@ -26,15 +27,18 @@ impl Board {
                 // House row
                 pad(y);
                 print!(" ");
-                for x in -I8_R - y ..= I8_R  {
+                for x in -r - y ..= r  {
                     // Prime vertices
                     let y = y - 1;
                     let z = -1 - x - y;
-                    let b = self.get_building(&Coordinate3::new(x, y, z));
+                    let coord = VertexCoord::new(x, y, z);
+                    let b = self.get_building(&coord);
                     let (building, player) = Building::from_u8(b);
 
-                    if player == 0 {
-                        print!("   {}  ", "x");
+                    if !self.is_valid_vertex(&coord) {
+                        print!("      ");
+                    } else if player == 0 {
+                        print!("   x  ");
                     } else {
                         print!("   {}  ", building.as_notation().color(player_color(player)));
                     }
@@ -43,11 +47,11 @@ impl Board {
 
                 pad(y);
                 print!("  ");
-                for x in -I8_R ..= I8_R {
+                for x in -r ..= r {
                     let coord: Coordinate2 = Coordinate2::new(x, y);
                     let z: i8 = -x - y;
-                    if coord.is_valid_tile() {
-                        let road =  self.get_road(X_EDGE, &Coordinate2::new(y - 1, z + 1));
+                    if self.is_valid_tile(&coord) {
+                        let road =  self.get_road(&EdgeCoord::new(X_EDGE, y - 1, z + 1));
                         let x_color = if road != 0 {
                             player_color(road)
                         } else {
@@ -55,7 +59,7 @@ impl Board {
                         };
                         print!("{}", "/".color(x_color).bold());
                         print!("   ");
-                        let road =  self.get_road(Z_EDGE, &Coordinate2::new(x, y));
+                        let road =  self.get_road(&EdgeCoord::new(Z_EDGE, x, y));
                         let z_color = if road != 0 {
                             player_color(road)
                         } else {
@@ -70,14 +74,17 @@ impl Board {
                 // House row
                 pad(y);
                 print!(" ");
-                for x in -I8_R - y ..= I8_R + 1  {
+                for x in -r - y ..= r + 1  {
                     // Not prime vertices
                     let z = 1 - x - y;
-                    let b = self.get_building(&Coordinate3::new(x, y, z));
+                    let coord = VertexCoord::new(x, y, z);
+                    let b = self.get_building(&coord);
                     let (building, player) = Building::from_u8(b);
 
-                    if player == 0 {
-                        print!("{}     ", "x");
+                    if !self.is_valid_vertex(&coord) {
+                        print!("      ");
+                    } else if player == 0 {
+                        print!("x     ");
                     } else {
                         print!("{}     ", building.as_notation().color(player_color(player)));
                     }
@@ -88,13 +95,13 @@ impl Board {
             
             pad(y);
 
-            for x in -I8_R ..= I8_R {
+            for x in -r ..= r {
                 let z = -x - y;
                 let coord: Coordinate2 = Coordinate2::new(x, y);
-                if coord.is_valid_tile() {
+                if self.is_valid_tile(&coord) {
 
                     // Print next roads
-                    let road =  self.get_road(Y_EDGE, &Coordinate2::new(-y - x, x));
+                    let road =  self.get_road(&EdgeCoord::new(Y_EDGE, -y - x, x));
                     let color = if road != 0 {
                         player_color(road)
                     } else {
@@ -110,16 +117,8 @@ impl Board {
                         print!("{}", str);
                     }
 
-                    if x == I8_R {
-                        let road =  self.get_road(Y_EDGE, &Coordinate2::new(z - 1, x + 1));
-                        let color = if road != 0 {
-                            player_color(road)
-                        } else {
-                            Color::BrightBlack
-                        };
-                        print!(" {}", "|".color(color).bold());
-                    } else if z == -I8_R {
-                        let road =  self.get_road(Y_EDGE, &Coordinate2::new(z - 1, x + 1));
+                    if !self.is_valid_tile(&Coordinate2::new(x + 1, y)) {
+                        let road =  self.get_road(&EdgeCoord::new(Y_EDGE, z - 1, x + 1));
                         let color = if road != 0 {
                             player_color(road)
                         } else {
@@ -136,14 +135,17 @@ impl Board {
                 // House row
                 pad(y);
                 print!(" ");
-                for x in -I8_R - 1 ..= I8_R - y  {
+                for x in -r - 1 ..= r - y  {
                     // Prime vertices
                     let z = -1 - x - y;
-                    let b = self.get_building(&Coordinate3::new(x, y, z));
+                    let coord = VertexCoord::new(x, y, z);
+                    let b = self.get_building(&coord);
                     let (building, player) = Building::from_u8(b);
 
-                    if player == 0 {
-                        print!("{}     ", "x");
+                    if !self.is_valid_vertex(&coord) {
+                        print!("      ");
+                    } else if player == 0 {
+                        print!("x     ");
                     } else {
                         print!("{}     ", building.as_notation().color(player_color(player)));
                     }
@@ -152,11 +154,11 @@ impl Board {
 
                 pad(y);
                 print!("  ");
-                for x in -I8_R ..= I8_R {
+                for x in -r ..= r {
                     let coord: Coordinate2 = Coordinate2::new(x, y);
                     let z = -y - x;
-                    if coord.is_valid_tile() {
-                        let road =  self.get_road(Z_EDGE, &Coordinate2::new(x - 1, y + 1));
+                    if self.is_valid_tile(&coord) {
+                        let road =  self.get_road(&EdgeCoord::new(Z_EDGE, x - 1, y + 1));
                         let x_color = if road != 0 {
                             player_color(road)
                         } else {
@@ -164,7 +166,7 @@ impl Board {
                         };
                         print!("{}", "\\".color(x_color).bold());
                         print!("   ");
-                        let road =  self.get_road(X_EDGE, &Coordinate2::new(y, z));
+                        let road =  self.get_road(&EdgeCoord::new(X_EDGE, y, z));
                         let z_color = if road != 0 {
                             player_color(road)
                         } else {
@@ -179,15 +181,18 @@ impl Board {
                 // House row
                 pad(y);
                 print!(" ");
-                for x in -I8_R ..= I8_R - y {
+                for x in -r ..= r - y {
                     // Prime vertices
                     let y = y + 1;
                     let z = 1 - x - y;
-                    let b = self.get_building(&Coordinate3::new(x, y, z));
+                    let coord = VertexCoord::new(x, y, z);
+                    let b = self.get_building(&coord);
                     let (building, player) = Building::from_u8(b);
 
-                    if player == 0 {
-                        print!("   {}  ", "x");
+                    if !self.is_valid_vertex(&coord) {
+                        print!("      ");
+                    } else if player == 0 {
+                        print!("   x  ");
                     } else {
                         print!("   {}  ", building.as_notation().color(player_color(player)));
                     }
@@ -213,12 +218,6 @@ impl Display for Coordinate2 {
 } 
 
 
-impl Display for Coordinate3 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "({}, {}, {})", self.x, self.y, self.z)
-    }
-}
-
 impl Tile {
 
     fn get_color(&self) -> Color {
@@ -230,7 +229,6 @@ impl Tile {
             Tile::Sheep => Color::BrightGreen,
             Tile::Empty => Color::Blue,
             Tile::Lumber => Color::Green,
-            _ => unimplemented!(),
         }
     }
 
@@ -242,6 +240,8 @@ pub fn player_color(player: u8) -> Color {
         2 => Color::Red,
         3 => Color::Yellow,
         4 => Color::Green,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
         _ => unimplemented!(),
     }
 }
\ No newline at end of file
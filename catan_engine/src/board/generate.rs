@@ -6,10 +6,8 @@ use rand_seeder::Seeder;
 use rand_pcg::Pcg64;
 
 use crate::board::coordinate::Coordinate2;
-use crate::board::I8_R;
-use crate::board::USIZE_R;
 
-use super::{Board, Tile, tile};
+use super::{port::Port, tile::{self, Resource}, Board, Layout, Tile, STANDARD_RADIUS};
 
 const SHEEP_FREQUENCY: u8 = 4;
 const WHEAT_FREQUENCY: u8 = 4;
@@ -18,6 +16,41 @@ const BRICK_FREQUENCY: u8 = 3;
 const ORE_FREQUENCY: u8 = 3;
 const DESERT_FREQUENCY: u8 = 1;
 
+// 3:1 ports, the rest are one 2:1 port for each resource
+const THREE_PORT_FREQUENCY: u8 = 4;
+
+// Number values of the standard board
+const TILE_VALUES: [u8; 18] = [2, 3, 3, 4, 4, 5, 5, 6, 6, 8, 8, 9, 9, 10, 10, 11, 11, 12];
+
+// Tiles of the 5-6 player extension
+const EXTENSION_SHEEP_FREQUENCY: u8 = 6;
+const EXTENSION_WHEAT_FREQUENCY: u8 = 6;
+const EXTENSION_LUMBER_FREQUENCY: u8 = 6;
+const EXTENSION_BRICK_FREQUENCY: u8 = 5;
+const EXTENSION_ORE_FREQUENCY: u8 = 5;
+const EXTENSION_DESERT_FREQUENCY: u8 = 2;
+
+// 3:1 ports of the extension, the rest are one 2:1 port for each resource and an extra sheep port
+const EXTENSION_THREE_PORT_FREQUENCY: u8 = 5;
+
+// Steps the search for a placement of the values takes before new tiles are generated
+const MAX_VALUE_STEPS: u32 = 10_000;
+
+// Number values of the 5-6 player extension
+const EXTENSION_TILE_VALUES: [u8; 28] = [
+    2, 2, 3, 3, 3, 4, 4, 4, 5, 5, 5, 6, 6, 6, 8, 8, 8, 9, 9, 9, 10, 10, 10, 11, 11, 11, 12, 12,
+];
+
+/**
+ * Rule for placing the 2:1 ports relative to the tiles they touch
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PortPlacement {
+    Random,
+    AwayFromResource, // A 2:1 port may not touch a tile of its own resource
+    NextToResource, // A 2:1 port must touch a tile of its own resource
+}
+
 impl Board {
         /**
      * Create a random board without providing a seed
@@ -35,60 +68,171 @@ impl Board {
      * Generate a random board
      */
     pub fn random_from_seed(seed: String) -> Board {
+        Self::random_from_seed_with_ports(seed, PortPlacement::Random)
+    }
 
+    /**
+     * Generate a random board, placing the ports with a placement rule. If the values
+     * or the ports can not be placed on the generated tiles, new tiles are generated.
+     */
+    pub fn random_from_seed_with_ports(seed: String, placement: PortPlacement) -> Board {
+        Self::random_from_seed_with_layout(seed, Layout::Hexagon(STANDARD_RADIUS), placement)
+    }
+
+    /**
+     * Generate a random board with a layout. The 5-6 player extension uses its own tiles,
+     * values and ports. Hexagons of other sizes than the standard board repeat the standard
+     * tiles, values and ports as many times as they fit, and fill the rest with a random
+     * selection of them.
+     */
+    pub fn random_from_seed_with_layout(seed: String, layout: Layout, placement: PortPlacement) -> Board {
         let rng: &mut Pcg64 = &mut Seeder::from(&seed).make_rng();
-        
-        let mut bag = Vec::<Tile>::new();
+        Self::random_from_rng(rng, layout, placement)
+    }
 
-        // Add the tiles
-        for _ in 0 .. SHEEP_FREQUENCY {
-            bag.push(Tile::Sheep);
+    /**
+     * Generate a random board from a random number generator, so the board can share
+     * a seed with the rest of the game.
+     */
+    pub fn random_from_rng(rng: &mut Pcg64, layout: Layout, placement: PortPlacement) -> Board {
+        loop {
+            let mut board = match Self::random_tiles(rng, layout) {
+                Some(board) => board,
+                None => continue,
+            };
+            if board.set_random_ports(rng, placement) {
+                return board;
+            }
         }
+    }
 
-        for _ in 0 .. WHEAT_FREQUENCY {
-            bag.push(Tile::Wheat);
-        }
+    /**
+     * Shuffle the standard set of ports into the port locations. Returns false if
+     * there is no way to place the ports with the placement rule.
+     */
+    fn set_random_ports(&mut self, rng: &mut Pcg64, placement: PortPlacement) -> bool {
+        let mut standard = match self.layout {
+            Layout::Extension => {
+                let mut ports = vec![Port::Three; EXTENSION_THREE_PORT_FREQUENCY as usize];
+                ports.push(Port::Resource(Resource::Sheep));
+                ports
+            }
+            Layout::Hexagon(_) => vec![Port::Three; THREE_PORT_FREQUENCY as usize],
+        };
+        standard.extend(Resource::ALL.map(Port::Resource));
+        let mut bag = fill_bag(rng, &standard, self.port_locations.len());
+        bag.shuffle(rng);
 
-        for _ in 0 .. LUMBER_FREQUENCY {
-            bag.push(Tile::Lumber);
-        }
+        // The resources of the tiles touched by each port location
+        let touching: Vec<Vec<Resource>> = self.port_locations.iter().map(|e| {
+            Self::get_edge_adjacent_vertex_coords(e).iter().flat_map(|v| {
+                Self::get_vertex_adjacent_tile_coords(v)
+            }).filter(|e| self.is_valid_tile(e)).filter_map(|e| {
+                Tile::from_u8(self.get_tile(&e)).0.as_resource()
+            }).collect()
+        }).collect();
 
-        for _ in 0 .. BRICK_FREQUENCY {
-            bag.push(Tile::Brick);
-        }
+        // Back tracking to place the ports in order, trying the bag in its shuffled order
+        fn next(ports: &mut [Port], touching: &[Vec<Resource>], bag: &mut Vec<Port>, placement: PortPlacement, i: usize) -> bool {
+            if i == ports.len() {
+                return true
+            }
 
-        for _ in 0 .. ORE_FREQUENCY {
-            bag.push(Tile::Ore);
-        }
+            for j in 0 .. bag.len() {
+                let port = bag[j];
 
-        for _ in 0 .. DESERT_FREQUENCY {
-            bag.push(Tile::Desert);
+                // Only try each port type once at this location
+                if bag[.. j].contains(&port) {
+                    continue;
+                }
+
+                let valid = match (port, placement) {
+                    (Port::Three, _) | (_, PortPlacement::Random) => true,
+                    (Port::Resource(res), PortPlacement::AwayFromResource) => !touching[i].contains(&res),
+                    (Port::Resource(res), PortPlacement::NextToResource) => touching[i].contains(&res),
+                };
+
+                if valid {
+                    ports[i] = bag.remove(j);
+                    if next(ports, touching, bag, placement, i + 1) {
+                        return true
+                    }
+                    bag.insert(j, port);
+                }
+            }
+
+            false
         }
 
-        // Shuffle the bag
-        bag.shuffle(rng);
-        
+        next(&mut self.ports, &touching, &mut bag, placement, 0)
+    }
+
+    /**
+     * Generate the random tiles and values of a board. Returns None if the values can not
+     * be placed without 2 equal values, or a 6 and an 8, being next to each other.
+     */
+    fn random_tiles(rng: &mut Pcg64, layout: Layout) -> Option<Board> {
+
         // Create a new board
-        let mut board = Board::new();
-        let mut valid_coords = Self::get_tile_coords();
+        let mut board = Board::with_layout(layout);
+        let mut valid_coords = board.get_tile_coords().to_vec();
+
+        let (frequencies, values) = match layout {
+            Layout::Extension => ([
+                (Tile::Sheep, EXTENSION_SHEEP_FREQUENCY),
+                (Tile::Wheat, EXTENSION_WHEAT_FREQUENCY),
+                (Tile::Lumber, EXTENSION_LUMBER_FREQUENCY),
+                (Tile::Brick, EXTENSION_BRICK_FREQUENCY),
+                (Tile::Ore, EXTENSION_ORE_FREQUENCY),
+                (Tile::Desert, EXTENSION_DESERT_FREQUENCY),
+            ], &EXTENSION_TILE_VALUES[..]),
+            Layout::Hexagon(_) => ([
+                (Tile::Sheep, SHEEP_FREQUENCY),
+                (Tile::Wheat, WHEAT_FREQUENCY),
+                (Tile::Lumber, LUMBER_FREQUENCY),
+                (Tile::Brick, BRICK_FREQUENCY),
+                (Tile::Ore, ORE_FREQUENCY),
+                (Tile::Desert, DESERT_FREQUENCY),
+            ], &TILE_VALUES[..]),
+        };
+
+        // Add the tiles
+        let mut standard = Vec::<Tile>::new();
+        frequencies.iter().for_each(|(tile, frequency)| {
+            for _ in 0 .. *frequency {
+                standard.push(*tile);
+            }
+        });
+
+        // Fill the bag for the size of the board and shuffle
+        let mut bag = fill_bag(rng, &standard, valid_coords.len());
+        bag.shuffle(rng);
 
         // Set the tiles randomly
         valid_coords.iter().for_each(|coord| {
             let tile = bag.pop().unwrap();
-            board.tiles[(coord.x + I8_R) as usize][(coord.y + I8_R) as usize] = tile.as_value() << 4
+            board.set_tile(coord, tile, 0);
         });
 
-        // Create a bag of valid tile values and shuffle
-        let mut value_bag: Vec<u8> = vec![2, 3, 3, 4, 4, 5, 5, 6, 6, 8, 8, 9, 9, 10, 10, 11, 11, 12];
+        // Create a bag of valid tile values, one for every tile that is not a desert, and shuffle
+        let value_count = valid_coords.iter().filter(|e| {
+            Tile::from_u8(board.get_tile(e)).0 != Tile::Desert
+        }).count();
+        let mut value_bag = fill_bag(rng, values, value_count);
         value_bag.shuffle(rng);
 
         // Look ahead step for the back tracking algorithm to ensure 2 tile values
-        // are not adjacent 
-        fn next(board: &mut Board, coords: &mut Vec<Coordinate2>, values: &mut Vec<u8>) -> bool {
+        // are not adjacent. The search gives up after a number of steps, as some
+        // shuffles take far longer to place than generating new tiles.
+        fn next(board: &mut Board, coords: &mut Vec<Coordinate2>, values: &mut Vec<u8>, steps: &mut u32) -> bool {
             // Values may run out before the 
             if coords.is_empty() {
                 return true
             }
+            if *steps == 0 {
+                return false
+            }
+            *steps -= 1;
 
             let coord = coords.pop().unwrap();
             let (tile, _) = Tile::from_u8(board.get_tile(&coord));
@@ -96,7 +240,7 @@ impl Board {
             // Desert tile does not need a value
             if tile == Tile::Desert {
                 board.robber = coord.clone();
-                if next(board, coords, values) {
+                if next(board, coords, values, steps) {
                     return true;
                 }
                 board.robber = Coordinate2::new(0, 0);
@@ -118,7 +262,7 @@ impl Board {
                 }
                 visited[value as usize] = true;
 
-                let neighbors = Board::get_tile_neighbor_coords(&coord);
+                let neighbors = board.get_tile_neighbor_coords(&coord);
                 let mut valid = true;
 
                 // Check if the neighbors are valid
@@ -137,7 +281,7 @@ impl Board {
                 if valid {
                     let v = values.remove(i);
                     board.set_tile(&coord, tile, value);
-                    if next(board, coords, values)  {
+                    if next(board, coords, values, steps)  {
                         return true
                     }
                     values.insert(i, v);
@@ -151,9 +295,29 @@ impl Board {
             false
         }
 
-        next(&mut board, &mut valid_coords, &mut value_bag);
+        let mut steps = MAX_VALUE_STEPS;
+        if !next(&mut board, &mut valid_coords, &mut value_bag, &mut steps) {
+            return None
+        }
 
-        board
-        
+        Some(board)
+    }
+}
+
+/**
+ * Fill a bag of `count` items by repeating the standard set as many times as it fits,
+ * and adding a random selection from the standard set for the remainder
+ */
+fn fill_bag<T: Clone>(rng: &mut Pcg64, standard: &[T], count: usize) -> Vec<T> {
+    let mut bag = Vec::with_capacity(count);
+    for _ in 0 .. count / standard.len() {
+        bag.extend_from_slice(standard);
+    }
+    let remainder = count % standard.len();
+    if remainder > 0 {
+        let mut extra = standard.to_vec();
+        extra.shuffle(rng);
+        bag.extend(extra.into_iter().take(remainder));
     }
-}
\ No newline at end of file
+    bag
+}
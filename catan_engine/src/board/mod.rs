@@ -1,49 +1,127 @@
 
-mod generate;
+pub mod generate;
 mod notation;
 pub mod display;
 pub mod building;
 pub mod coordinate;
+pub mod port;
+pub mod road;
 pub mod tile;
+pub mod topology;
 
 use building::{Building, PLAYER_VALUE_MASK};
-use coordinate::{Coordinate2, Coordinate3};
+use coordinate::{Coordinate2, EdgeCoord, VertexCoord};
+use port::Port;
 use tile::Tile;
+use topology::{EdgeId, Topology, TileId, VertexId};
 
-const USIZE_R: usize = 2;
-const I8_R:i8 = USIZE_R as i8;
+// Radius of the standard board, not counting the center tile
+pub const STANDARD_RADIUS: i8 = 2;
 
+/**
+ * Edges of the port locations on the standard board, in clockwise order starting from
+ * the port touching the first tile (0, -2). This is the order the ports are listed in
+ * the notation.
+ */
 pub const PORT_LOCATIONS: [(i8, i8, i8); 9] = [
     (0, -3, 3),
+    (2, 1, -2),
+    (2, 2, -1),
     (1, -3, 3),
-    (2, -3, 3),
     (0, 1, -2),
-    (1, 1, -2),
-    (2, 1, -2),
     (0, 2, -1),
+    (2, -3, 3),
+    (1, 1, -2),
     (1, 2, -1),
-    (2, 2, -1),
 ];
 
+/**
+ * Shape of the board
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Layout {
+    Hexagon(i8), // Regular hexagon with a radius, not counting the center tile
+    Extension, // 30 tiles of the 5-6 player extension, in rows of 3, 4, 5, 6, 5, 4, 3
+}
+
+impl Layout {
+
+    /**
+     * Radius of the smallest hexagon containing the layout
+     */
+    pub fn get_radius(&self) -> i8 {
+        match *self {
+            Layout::Hexagon(radius) => radius,
+            Layout::Extension => 3,
+        }
+    }
+
+    /**
+     * Number of tiles in the layout
+     */
+    pub fn get_tile_count(&self) -> usize {
+        match *self {
+            Layout::Hexagon(r) => (3 * r as usize * (r as usize + 1)) + 1,
+            Layout::Extension => 30,
+        }
+    }
+
+}
+
+/**
+ * The tiles, roads and buildings are stored by the ids of their coordinates in the
+ * topology of the layout.
+ */
 pub struct Board {
-    pub tiles: [[u8; USIZE_R * 2 + 1]; USIZE_R * 2 + 1],
-    pub roads: [[[u8; (USIZE_R + 1) * 2 + 1]; (USIZE_R + 1) * 2 + 1]; 3],
-    pub buildings: [[[u8; (USIZE_R + 1) * 2 + 1]; (USIZE_R + 1) * 2 + 1]; (USIZE_R + 1) * 2 + 1],
-    pub ports: [u8; 9], // 9 port spots be default, will need to change this to support custom port locations
+    layout: Layout,
+    radius: i8,
+    topology: &'static Topology,
+    pub tiles: Vec<u8>,
+    pub roads: Vec<u8>,
+    pub buildings: Vec<u8>,
+    pub ports: Vec<Port>,
+    port_locations: Vec<EdgeCoord>,
+    port_vertices: Vec<[VertexId; 2]>, // The vertices served by each port, the ends of its edge
     pub robber: Coordinate2,
 }
 
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Board {
+
+    /**
+     * The radius of the board, not counting the center tile
+     */
+    pub fn get_radius(&self) -> i8 {
+        self.radius
+    }
+
+    /**
+     * The shape of the board
+     */
+    pub fn get_layout(&self) -> Layout {
+        self.layout
+    }
+
+    /**
+     * The ids and adjacency of the tiles, vertices and edges of the board
+     */
+    pub fn get_topology(&self) -> &'static Topology {
+        self.topology
+    }
     
     /**
      * Set a tile in the grid from the Tile enum and a value. This is encoded into a 1 byte representation
      * of the tile and value.
      */
     pub fn set_tile(&mut self, coord: &Coordinate2, tile: Tile, value: u8) {
-        if coord.is_valid_tile() && Self::is_tile_value(value) {
-            self.tiles[(coord.x + I8_R) as usize][(coord.y + I8_R) as usize] = tile.as_value() << 4 | value;
-        } else {
-            panic!("Invalid coordinates while setting tile. {} with value {}", coord, value);
+        match self.topology.get_tile_id(coord) {
+            Some(i) if Self::is_tile_value(value) => self.tiles[i] = tile.as_value() << 4 | value,
+            _ => panic!("Invalid coordinates while setting tile. {} with value {}", coord, value),
         }
     }
 
@@ -53,38 +131,68 @@ impl Board {
      * [4 bits: Tile type][4 bits: Tile Value]
      */
     pub fn get_tile(&self, coord: &Coordinate2) -> u8 {
-        if coord.is_valid_tile() {
-            return self.tiles[(coord.x + I8_R)as usize][(coord.y + I8_R) as usize]
+        match self.topology.get_tile_id(coord) {
+            Some(i) => self.tiles[i],
+            None => panic!("Invalid coordinates while getting tile"),
         }
-        panic!("Invalid coordinates while getting tile");
     }
 
     /**
-     * Returns the player 1 - 4 if they own the road, or 0 if there is no road
+     * Returns the encoded form of the tile with an id
      */
-    pub fn get_road(&self, axis: i8, coord: &Coordinate2) -> u8 {
-        return self.roads[axis as usize][(coord.x + I8_R + 1) as usize][(coord.y + I8_R + 1) as usize]
+    pub fn get_tile_by_id(&self, tile: TileId) -> u8 {
+        self.tiles[tile]
     }
 
-    pub fn set_road(&mut self, coord: &Coordinate3, player: u8) {
-        self.roads[coord.x as usize][(coord.y + I8_R + 1) as usize][(coord.z + I8_R + 1) as usize] = player;
+    /**
+     * Returns the player 1 - 4 if they own the road, or 0 if there is no road.
+     * Edges off the board never have a road.
+     */
+    pub fn get_road(&self, coord: &EdgeCoord) -> u8 {
+        self.topology.get_edge_id(coord).map_or(0, |e| self.roads[e])
+    }
+
+    /**
+     * Returns the owner of the road on the edge with an id, or 0 if there is no road
+     */
+    pub fn get_road_by_id(&self, edge: EdgeId) -> u8 {
+        self.roads[edge]
+    }
+
+    pub fn set_road(&mut self, coord: &EdgeCoord, player: u8) {
+        match self.topology.get_edge_id(coord) {
+            Some(i) => self.roads[i] = player,
+            None => panic!("Invalid coordinates while setting road. {}", coord),
+        }
     }
 
     /**
-     * Returns the encoded road data for a specific vertex
+     * Returns the encoded building data for a specific vertex. Vertices off the board
+     * never have a building.
      */
-    pub fn get_building(&self, coord: &Coordinate3) -> u8 {
-        return self.buildings[(coord.x + I8_R + 1) as usize][(coord.y + I8_R + 1) as usize][(coord.z + I8_R + 1) as usize]
+    pub fn get_building(&self, coord: &VertexCoord) -> u8 {
+        self.topology.get_vertex_id(coord).map_or(0, |v| self.buildings[v])
+    }
+
+    /**
+     * Returns the encoded building data for the vertex with an id
+     */
+    pub fn get_building_by_id(&self, vertex: VertexId) -> u8 {
+        self.buildings[vertex]
     }
 
     /**
      * Set a building
      */
-    pub fn set_building(&mut self, coord: &Coordinate3, building: Building, player: u8) {
+    pub fn set_building(&mut self, coord: &VertexCoord, building: Building, player: u8) {
+        let i = match self.topology.get_vertex_id(coord) {
+            Some(i) => i,
+            None => panic!("Invalid coordinates while setting building. {}", coord),
+        };
         if building == Building::Empty {
-            self.buildings[(coord.x + I8_R + 1) as usize][(coord.y + I8_R + 1) as usize][(coord.z + I8_R + 1) as usize] = 0;
+            self.buildings[i] = 0;
         } else {
-            self.buildings[(coord.x + I8_R + 1) as usize][(coord.y + I8_R + 1) as usize][(coord.z + I8_R + 1) as usize] = building.as_value() << 4 | player;
+            self.buildings[i] = building.as_value() << 4 | player;
         }
     }
 
@@ -94,83 +202,157 @@ impl Board {
      * Note: 0 is considered valid to represent tiles without a value (e.g desert)
      */
     pub fn is_tile_value(v: u8) -> bool {
-        return (v >= 2 && v <= 12) || v == 0
+        (2 ..= 12).contains(&v) || v == 0
     }
 
     /**
      * Create a new blank board
      */
     pub fn new() -> Self {
-        Board {
-            tiles: [[0; USIZE_R * 2 + 1]; USIZE_R * 2 + 1],
-            roads: [[[0; (USIZE_R + 1) * 2 + 1]; (USIZE_R + 1) * 2 + 1]; 3],
-            buildings: [[[0; (USIZE_R + 1) * 2 + 1]; (USIZE_R + 1) * 2 + 1]; (USIZE_R + 1) * 2 + 1],
+        Self::with_radius(STANDARD_RADIUS)
+    }
+
+    /**
+     * Create a new blank board with a radius, not counting the center tile
+     */
+    pub fn with_radius(radius: i8) -> Self {
+        Self::with_layout(Layout::Hexagon(radius))
+    }
+
+    /**
+     * Create a new blank board with a layout
+     */
+    pub fn with_layout(layout: Layout) -> Self {
+        let topology = Topology::of(layout);
+        let mut board = Board {
+            layout,
+            radius: layout.get_radius(),
+            topology,
+            tiles: vec![0; topology.get_tiles().len()],
+            roads: vec![0; topology.get_edges().len()],
+            buildings: vec![0; topology.get_vertices().len()],
             robber: Coordinate2::new(0, 0),
-            ports: [0; 9],
+            ports: Vec::new(),
+            port_locations: Vec::new(),
+            port_vertices: Vec::new(),
+        };
+        board.port_locations = board.get_default_port_locations();
+        board.port_vertices = board.port_locations.iter().map(|e| {
+            *topology.get_edge_vertices(topology.get_edge_id(e).unwrap())
+        }).collect();
+        board.ports = vec![Port::Three; board.port_locations.len()];
+        board
+    }
+
+    /**
+     * Get the port locations for the layout of this board. Boards other than the standard
+     * board space the ports evenly along the coast, 3 ports for every 10 coast edges,
+     * in clockwise order starting from the first tile.
+     */
+    fn get_default_port_locations(&self) -> Vec<EdgeCoord> {
+        if self.layout == Layout::Hexagon(STANDARD_RADIUS) {
+            return PORT_LOCATIONS.iter().map(|(x, y, z)| EdgeCoord::new(*x, *y, *z)).collect()
+        }
+
+        // Screen position of a tile, y grows downwards so angles grow clockwise
+        let position = |e: &Coordinate2| -> (f32, f32) {
+            (e.x as f32 + e.y as f32 / 2.0, e.y as f32)
+        };
+
+        // Angle around the center of the board to the middle of an edge
+        let tiles = self.get_tile_coords();
+        let count = tiles.len() as f32;
+        let (cx, cy) = tiles.iter().map(position).fold((0.0, 0.0), |(cx, cy), (px, py)| {
+            (cx + px / count, cy + py / count)
+        });
+        let angle = |coord: &EdgeCoord| -> f32 {
+            let (px, py) = Self::get_edge_adjacent_tile_coords(coord).iter().map(position).fold((0.0, 0.0), |(x, y), (px, py)| {
+                (x + px / 2.0, y + py / 2.0)
+            });
+            (py - cy).atan2(px - cx)
+        };
+
+        let mut coast = self.get_coast_edge_coords();
+        if coast.is_empty() {
+            return coast
         }
+
+        // Start half a coast edge before the first tile, so its edges are not split
+        let (px, py) = position(&tiles[0]);
+        let start = (py - cy).atan2(px - cx) - std::f32::consts::PI / coast.len() as f32;
+        let tau = std::f32::consts::TAU;
+        coast.sort_by(|a, b| {
+            let a = (angle(a) - start).rem_euclid(tau);
+            let b = (angle(b) - start).rem_euclid(tau);
+            a.total_cmp(&b)
+        });
+
+        let count = coast.len() * 3 / 10;
+        (0 .. count).map(|i| coast[i * coast.len() / count]).collect()
     }
 
     /**
      * Get all the tiles (as u8 encoded tiles) around a vertex 
      */
-    pub fn get_vertex_adjacent_tiles(&self, coord: &Coordinate3) -> Vec<u8> {
-        Self::get_vertex_adjacent_tile_coords(coord).iter().filter(|e| {
-           e.is_valid_tile()
-        }).map(|e| {
-            self.get_tile(e)
-        }).collect()
+    pub fn get_vertex_adjacent_tiles(&self, coord: &VertexCoord) -> Vec<u8> {
+        match self.topology.get_vertex_id(coord) {
+            Some(v) => self.topology.get_vertex_tiles(v).iter().map(|e| self.tiles[*e]).collect(),
+            None => Vec::new(),
+        }
     }
 
     /**
      * Get valid building vertex coordinates for the whole board
      */
-    pub fn get_valid_starting_buiding_coords(&self) -> Vec<Coordinate3> {
-        Self::get_vertex_coords().into_iter().filter(|e| {
-            self.is_valid_building_coord(&e)
-        }).collect()
+    pub fn get_valid_starting_buiding_coords(&self) -> impl Iterator<Item = VertexCoord> + '_ {
+        (0 .. self.buildings.len()).filter(|v| {
+            self.is_valid_building_vertex(*v)
+        }).map(|v| self.topology.get_vertices()[v])
     }
 
     /**
      * Detect if the coordinate can accept a building, which much be
      * no less that 2 edges away from another building
      */
-    pub fn is_valid_building_coord(&self, coord: &Coordinate3) -> bool {
-
-        if self.has_building(coord) {
-            return false;
-        }
+    pub fn is_valid_building_coord(&self, coord: &VertexCoord) -> bool {
+        self.topology.get_vertex_id(coord).is_some_and(|v| self.is_valid_building_vertex(v))
+    }
 
-        // Get the adjoining vertex in all directions
-        for d in 0 ..= 2 {
-            let v: Coordinate3 = Self::get_prime_pair_vertex(d, coord);
-            if v.is_valid_vertex() && self.has_building(&v) {
-                return false
-            }
-        }
-        true
+    /**
+     * Detect if the vertex with an id can accept a building, neither it or its
+     * neighbors may have a building
+     */
+    pub fn is_valid_building_vertex(&self, vertex: VertexId) -> bool {
+        !self.has_building(vertex) && !self.topology.get_vertex_neighbors(vertex).iter().any(|v| {
+            self.has_building(*v)
+        })
     }
 
     /**
      * Determine if a Vertex has a building
      */
-    fn has_building(&self, coord: &Coordinate3) -> bool {
-        self.get_building(coord) & PLAYER_VALUE_MASK != 0
+    fn has_building(&self, vertex: VertexId) -> bool {
+        self.buildings[vertex] & PLAYER_VALUE_MASK != 0
     }
 
     /**
      * During initial placement, get the road spots around a settlement
      */
-    pub fn get_placement_roads_around(&self, coord: &Coordinate3) -> Vec<Coordinate3> {
-        Self::get_vertex_adjacent_edge_coords(coord).into_iter().filter(|e| {
-            e.is_valid_edge() && !self.has_road(e.x, &Coordinate2::new(e.y, e.z))
-        }).collect()
+    pub fn get_placement_roads_around(&self, coord: &VertexCoord) -> impl Iterator<Item = EdgeCoord> + '_ {
+        let edges = match self.topology.get_vertex_id(coord) {
+            Some(v) => self.topology.get_vertex_edges(v),
+            None => &[],
+        };
+        edges.iter().filter(|e| {
+            !self.has_road(**e)
+        }).map(|e| self.topology.get_edges()[*e])
     }
 
     /**
      * Determine if an edge has a road
      */
-    fn has_road(&self, dir: i8, coord: &Coordinate2) -> bool {
-        self.get_road(dir, coord) & PLAYER_VALUE_MASK != 0
+    fn has_road(&self, edge: EdgeId) -> bool {
+        self.roads[edge] & PLAYER_VALUE_MASK != 0
     }
 
 }
\ No newline at end of file
@@ -1,4 +1,12 @@
-use super::{tile::Tile, Board};
+use std::{fmt::Display, str::FromStr};
+
+use super::{coordinate::{Coordinate2, EdgeCoord, ParseCoordinateError, VertexCoord, X_EDGE, Y_EDGE, Z_EDGE}, port::Port, tile::{Resource, Tile}, Board, Layout};
+
+// Notation character for the 3:1 ports
+const THREE_PORT_NOTATION: char = 'T';
+
+// Largest board radius that can be read from notation
+const MAX_RADIUS: i8 = 5;
 
 impl Board {
 
@@ -7,29 +15,38 @@ impl Board {
      */
     pub fn as_notation(self: &Board) -> String {
         let mut value  = String::new();
-        Self::get_tile_coords().iter().for_each(|coord| {
+        self.get_tile_coords().iter().for_each(|coord| {
             let (tile, v) = Tile::from_u8(self.get_tile(coord));
-            value.push_str(format!("{}{}", tile.as_notation(), v).as_str());
+            if tile == Tile::Desert {
+                value.push_str(&tile.as_notation());
+            } else {
+                value.push_str(format!("{}{}", tile.as_notation(), v).as_str());
+            }
         });
         value
     }
 
     /**
-     * Create a board from notation
+     * Create a board from notation. The robber starts on the first desert. The layout of
+     * the board is found from the number of tiles.
      */
     pub fn from_notation(str: String) -> Board {
-        let mut board = Board::new();
-
         let mut character = 0;
         let mut tile = Tile::Empty;
         let mut value: u8 = 0;
 
         let mut tiles: Vec<(Tile, u8)> = Vec::new();
 
-        str.chars().into_iter().for_each(|c| {
+        str.chars().filter(|c| !Self::is_ignored_notation(*c)).for_each(|c| {
             match tile {
                 Tile::Empty => {
                     tile = Tile::from_string(&c.to_string());
+
+                    // The desert does not have a value
+                    if tile == Tile::Desert {
+                        tiles.push((tile, 0));
+                        tile = Tile::Empty;
+                    }
                 },
                 _ => {
                     if c.is_numeric() {
@@ -48,17 +65,232 @@ impl Board {
             }
             character += 1;
         });
-        
-        let coords =  Self::get_tile_coords();
-        if tiles.len() != coords.len() {
-            panic!("Invalid tile length for Board notation");
+
+        if tile != Tile::Empty {
+            panic!("Missing tile value at the end of the board notation");
         }
+        
+        let layout = (0 ..= MAX_RADIUS).map(Layout::Hexagon).chain([Layout::Extension]).find(|e| {
+            e.get_tile_count() == tiles.len()
+        });
+        let mut board = match layout {
+            Some(layout) => Board::with_layout(layout),
+            None => panic!("Invalid tile length for Board notation"),
+        };
+        let coords =  board.get_tile_coords();
 
-        coords.iter().zip(tiles.into_iter()).for_each(|(coord, (tile, value)) | {
+        // Set in reverse so the robber ends on the first desert
+        coords.iter().zip(tiles).rev().for_each(|(coord, (tile, value)) | {
             board.set_tile(coord, tile, value);
+            if tile == Tile::Desert {
+                board.robber = coord.clone();
+            }
         });
 
         board
     }
 
-}
\ No newline at end of file
+    /**
+     * Convert the ports to notation, in clockwise order from the first tile
+     */
+    pub fn ports_as_notation(&self) -> String {
+        self.ports.iter().map(|port| match port {
+            Port::Three => THREE_PORT_NOTATION.to_string(),
+            Port::Resource(res) => res.as_notation(),
+        }).collect()
+    }
+
+    /**
+     * Set the ports from notation, in clockwise order from the first tile
+     */
+    pub fn set_ports_from_notation(&mut self, str: &str) {
+        let ports: Vec<Port> = str.chars().filter(|c| !Self::is_ignored_notation(*c)).map(|c| {
+            if c == THREE_PORT_NOTATION {
+                Port::Three
+            } else {
+                Port::Resource(Resource::from_string(&c.to_string()))
+            }
+        }).collect();
+
+        if ports.len() != self.ports.len() {
+            panic!("Invalid port length for Board notation, expected {} ports but found {}: {}", self.ports.len(), ports.len(), str);
+        }
+
+        self.ports.copy_from_slice(&ports);
+    }
+
+    /**
+     * Whitespace and pipes are ignored in the notation
+     */
+    pub fn is_ignored_notation(c: char) -> bool {
+        c.is_whitespace() || c == '|'
+    }
+
+}
+
+impl Coordinate2 {
+
+    /**
+     * Convert a tile coordinate to notation: `(x,y)`
+     */
+    pub fn as_notation(&self) -> String {
+        format!("({},{})", self.x, self.y)
+    }
+
+    /**
+     * Create a tile coordinate from notation: `(x,y)`
+     */
+    pub fn from_notation(str: &str) -> Coordinate2 {
+        match parse_coordinate_values(str.strip_prefix('(').and_then(|e| e.strip_suffix(')')), 2, str) {
+            Ok(values) => Coordinate2::new(values[0], values[1]),
+            Err(err) => panic!("{}", err),
+        }
+    }
+
+}
+
+impl VertexCoord {
+
+    /**
+     * Convert a vertex coordinate to notation: `(x,y,z)`
+     */
+    pub fn as_notation(&self) -> String {
+        self.to_string()
+    }
+
+    /**
+     * Create a vertex coordinate from notation: `(x,y,z)`
+     */
+    pub fn from_notation(str: &str) -> VertexCoord {
+        str.parse().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+}
+
+/**
+ * A vertex is written as its 3 values: `(x,y,z)`
+ */
+impl Display for VertexCoord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({},{},{})", self.x, self.y, self.z)
+    }
+}
+
+impl FromStr for VertexCoord {
+    type Err = ParseCoordinateError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let values = parse_coordinate_values(str.strip_prefix('(').and_then(|e| e.strip_suffix(')')), 3, str)?;
+        VertexCoord::try_new(values[0], values[1], values[2]).ok_or_else(|| ParseCoordinateError(str.to_string()))
+    }
+}
+
+impl EdgeCoord {
+
+    /**
+     * Convert an edge coordinate to notation, prefixed by its axis: `Xy,z`
+     */
+    pub fn as_notation(&self) -> String {
+        self.to_string()
+    }
+
+    /**
+     * Create an edge coordinate from notation, prefixed by its axis: `Xy,z`
+     */
+    pub fn from_notation(str: &str) -> EdgeCoord {
+        str.parse().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+}
+
+/**
+ * An edge is written as its axis followed by its 2 values: `Xy,z`
+ */
+impl Display for EdgeCoord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let axis = match self.axis {
+            X_EDGE => 'X',
+            Y_EDGE => 'Y',
+            _ => 'Z',
+        };
+        write!(f, "{}{},{}", axis, self.y, self.z)
+    }
+}
+
+impl FromStr for EdgeCoord {
+    type Err = ParseCoordinateError;
+
+    fn from_str(str: &str) -> Result<Self, Self::Err> {
+        let axis = match str.chars().next() {
+            Some('X') => X_EDGE,
+            Some('Y') => Y_EDGE,
+            Some('Z') => Z_EDGE,
+            _ => return Err(ParseCoordinateError(str.to_string())),
+        };
+        let values = parse_coordinate_values(str.get(1 ..), 2, str)?;
+        Ok(EdgeCoord::new(axis, values[0], values[1]))
+    }
+}
+
+/**
+ * Parse a comma separated list of coordinate values
+ */
+fn parse_coordinate_values(values: Option<&str>, count: usize, str: &str) -> Result<Vec<i8>, ParseCoordinateError> {
+    let values = values.unwrap_or_default().split(',').map(|e| e.parse::<i8>()).collect::<Result<Vec<i8>, _>>();
+    match values {
+        Ok(values) if values.len() == count => Ok(values),
+        _ => Err(ParseCoordinateError(str.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::coordinate::{EdgeCoord, ParseCoordinateError, VertexCoord, X_EDGE, Z_EDGE};
+
+    #[test]
+    fn edge_round_trip() {
+        let edge = EdgeCoord::new(X_EDGE, -3, 3);
+        assert_eq!(edge.to_string(), "X-3,3");
+        assert_eq!("X-3,3".parse::<EdgeCoord>(), Ok(edge));
+        assert_eq!(EdgeCoord::from_notation("Z2,-1"), EdgeCoord::new(Z_EDGE, 2, -1));
+
+        for str in ["X0,0", "Y1,-2", "Z-4,4"] {
+            assert_eq!(str.parse::<EdgeCoord>().unwrap().to_string(), str);
+        }
+    }
+
+    #[test]
+    fn vertex_round_trip() {
+        let vertex = VertexCoord::new(0, -2, 3);
+        assert_eq!(vertex.to_string(), "(0,-2,3)");
+        assert_eq!("(0,-2,3)".parse::<VertexCoord>(), Ok(vertex));
+
+        for str in ["(1,0,0)", "(-1,0,0)", "(3,-1,-1)"] {
+            assert_eq!(str.parse::<VertexCoord>().unwrap().to_string(), str);
+        }
+    }
+
+    #[test]
+    fn invalid_notation_is_rejected() {
+        for str in ["Q1,2", "x1,2", "X1", "X1,2,3", "Xa,2", "X200,1", "", "1,2"] {
+            assert_eq!(str.parse::<EdgeCoord>(), Err(ParseCoordinateError(str.to_string())));
+        }
+        for str in ["(1,0,0", "1,0,0)", "(1,0)", "(1,0,0,0)", "(0,0,0)", "(1,1,0)", "(a,0,1)", ""] {
+            assert_eq!(str.parse::<VertexCoord>(), Err(ParseCoordinateError(str.to_string())));
+        }
+    }
+
+    #[test]
+    fn try_new_rejects_invalid_coordinates() {
+        // The values of a vertex add up to 1 or -1
+        assert_eq!(VertexCoord::try_new(0, 0, 0), None);
+        assert_eq!(VertexCoord::try_new(1, 1, 0), None);
+        assert_eq!(VertexCoord::try_new(127, 127, 127), None);
+        assert_eq!(VertexCoord::try_new(0, 0, -1), Some(VertexCoord::new(0, 0, -1)));
+
+        assert_eq!(EdgeCoord::try_new(-1, 0, 0), None);
+        assert_eq!(EdgeCoord::try_new(3, 0, 0), None);
+        assert_eq!(EdgeCoord::try_new(Z_EDGE, 1, -1), Some(EdgeCoord::new(Z_EDGE, 1, -1)));
+    }
+
+}
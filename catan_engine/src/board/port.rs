@@ -0,0 +1,58 @@
+use super::{building::PLAYER_VALUE_MASK, coordinate::{EdgeCoord, VertexCoord}, tile::Resource, Board};
+
+// Cards given for one card when trading with the bank without a port
+pub const BANK_RATE: u8 = 4;
+
+// Cards given for one card with a 3:1 port
+pub const THREE_PORT_RATE: u8 = 3;
+
+// Cards of the port's resource given for one card with a 2:1 port
+pub const RESOURCE_PORT_RATE: u8 = 2;
+
+/**
+ * A port lets the players with a building on it trade with the bank at a better rate
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Port {
+    Three, // 3:1 port for any resource
+    Resource(Resource), // 2:1 port for a single resource
+}
+
+impl Board {
+
+    /**
+     * Get the edges the ports are on, in the order the ports are listed in the notation
+     */
+    pub fn get_port_locations(&self) -> &[EdgeCoord] {
+        &self.port_locations
+    }
+
+    /**
+     * Get the 2 vertices served by a port, the ends of its edge
+     */
+    pub fn get_port_vertex_coords(&self, port: usize) -> [VertexCoord; 2] {
+        self.port_vertices[port].map(|v| self.get_topology().get_vertices()[v])
+    }
+
+    /**
+     * Get every port a player has a settlement or city on
+     */
+    pub fn get_player_ports(&self, player: u8) -> impl Iterator<Item = Port> + '_ {
+        self.ports.iter().zip(self.port_vertices.iter()).filter(move |(_, vertices)| {
+            vertices.iter().any(|v| self.get_building_by_id(*v) & PLAYER_VALUE_MASK == player)
+        }).map(|(port, _)| *port)
+    }
+
+    /**
+     * Get the number of cards of a resource a player gives the bank for one card
+     * of another resource
+     */
+    pub fn get_trade_rate(&self, player: u8, res: Resource) -> u8 {
+        self.get_player_ports(player).map(|port| match port {
+            Port::Resource(other) if other == res => RESOURCE_PORT_RATE,
+            Port::Resource(_) => BANK_RATE,
+            Port::Three => THREE_PORT_RATE,
+        }).min().unwrap_or(BANK_RATE)
+    }
+
+}
@@ -0,0 +1,51 @@
+use super::{building::PLAYER_VALUE_MASK, topology::{EdgeId, VertexId}, Board};
+
+impl Board {
+
+    /**
+     * Get the number of segments in the longest continuous road owned by a player.
+     * Roads may branch and loop, but each segment can only be counted once, and
+     * an opponent's settlement or city breaks the road at that vertex.
+     */
+    pub fn get_longest_road(&self, player: u8) -> u8 {
+        let mut visited: Vec<EdgeId> = Vec::new();
+        (0 .. self.buildings.len()).map(|v| {
+            self.get_longest_road_from(player, v, &mut visited)
+        }).max().unwrap_or(0)
+    }
+
+    /**
+     * Depth first search for the longest road leaving a vertex without reusing
+     * any of the visited edges
+     */
+    fn get_longest_road_from(&self, player: u8, vertex: VertexId, visited: &mut Vec<EdgeId>) -> u8 {
+        let topology = self.get_topology();
+        let mut longest = 0;
+        for (e, next) in topology.get_vertex_edges(vertex).iter().zip(topology.get_vertex_neighbors(vertex)) {
+            if self.roads[*e] != player || visited.contains(e) {
+                continue;
+            }
+
+            // Follow the road to the vertex on the other side of the edge
+            visited.push(*e);
+            let length = if self.is_opponent_building(player, *next) {
+                1
+            } else {
+                1 + self.get_longest_road_from(player, *next, visited)
+            };
+            visited.pop();
+
+            longest = longest.max(length);
+        }
+        longest
+    }
+
+    /**
+     * Determine if a vertex has a building owned by another player
+     */
+    pub fn is_opponent_building(&self, player: u8, vertex: VertexId) -> bool {
+        let owner = self.buildings[vertex] & PLAYER_VALUE_MASK;
+        owner != 0 && owner != player
+    }
+
+}
@@ -13,6 +13,64 @@ pub const SHEEP_VALUE: u8   = 4;
 pub const WHEAT_VALUE: u8   = 5;
 pub const DESERT_VALUE: u8  = 6;
 
+/**
+ * A resource card, produced by the tile of the same name. The value of a resource is
+ * the value of its tile, and the resources are declared in that order.
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Resource {
+    Brick, Lumber, Ore, Sheep, Wheat
+}
+
+impl Resource {
+
+    // Every resource, in the order of their values
+    pub const ALL: [Resource; 5] = [Resource::Brick, Resource::Lumber, Resource::Ore, Resource::Sheep, Resource::Wheat];
+
+    pub fn as_notation(&self) -> String {
+        match self {
+            Resource::Brick => "B".to_string(),
+            Resource::Lumber => "L".to_string(),
+            Resource::Ore => "O".to_string(),
+            Resource::Sheep => "S".to_string(),
+            Resource::Wheat => "W".to_string(),
+        }
+    }
+
+    pub fn from_string(c: &str) -> Resource {
+        match c {
+            "B" => Resource::Brick,
+            "L" => Resource::Lumber,
+            "O" => Resource::Ore,
+            "S" => Resource::Sheep,
+            "W" => Resource::Wheat,
+            _ => panic!("Invalid resource: {}", c),
+        }
+    }
+
+    pub fn as_value(&self) -> u8 {
+        match self {
+            Resource::Brick => BRICK_VALUE,
+            Resource::Lumber => LUMBER_VALUE,
+            Resource::Ore => ORE_VALUE,
+            Resource::Sheep => SHEEP_VALUE,
+            Resource::Wheat => WHEAT_VALUE,
+        }
+    }
+
+    pub fn from_value(value: u8) -> Resource {
+        match value {
+            BRICK_VALUE => Resource::Brick,
+            LUMBER_VALUE => Resource::Lumber,
+            ORE_VALUE => Resource::Ore,
+            SHEEP_VALUE => Resource::Sheep,
+            WHEAT_VALUE => Resource::Wheat,
+            _ => panic!("Invalid resource value: {}", value),
+        }
+    }
+
+}
+
 impl Tile {
 
     pub fn as_notation(&self) -> String {
@@ -27,8 +85,8 @@ impl Tile {
         }
     }
 
-    pub fn from_string(c: &String) -> Tile {
-        match c.as_str() {
+    pub fn from_string(c: &str) -> Tile {
+        match c {
             "B" => Tile::Brick,
             "D" => Tile::Desert,
             "L" => Tile::Lumber,
@@ -51,6 +109,20 @@ impl Tile {
         }
     }
 
+    /**
+     * The resource produced by the tile, if it produces one
+     */
+    pub fn as_resource(&self) -> Option<Resource> {
+        match self {
+            Tile::Brick => Some(Resource::Brick),
+            Tile::Lumber => Some(Resource::Lumber),
+            Tile::Ore => Some(Resource::Ore),
+            Tile::Sheep => Some(Resource::Sheep),
+            Tile::Wheat => Some(Resource::Wheat),
+            Tile::Desert | Tile::Empty => None,
+        }
+    }
+
     pub fn from_u8(tile: u8) -> (Tile, u8) {
         let v = tile & TILE_VALUE_MASK;
         let t = tile >> TILE_SHIFT;
@@ -0,0 +1,279 @@
+use std::sync::Mutex;
+
+use super::{coordinate::{Coordinate2, EdgeCoord, VertexCoord}, Board, Layout};
+
+pub type TileId = usize;
+pub type VertexId = usize;
+pub type EdgeId = usize;
+
+// Topologies already built, one for each layout that has been used
+static TOPOLOGIES: Mutex<Vec<&'static Topology>> = Mutex::new(Vec::new());
+
+/**
+ * The tiles, vertices and edges of a layout numbered with dense ids, and the adjacency
+ * between them. The ids follow the order the coordinates are listed in: tiles by row,
+ * vertices by (x, y, z) and edges by axis then (y, z). The standard board has 19 tiles,
+ * 54 vertices and 72 edges.
+ *
+ * The tables are built once for each layout and shared by every board with that layout.
+ */
+pub struct Topology {
+    layout: Layout,
+    tiles: Vec<Coordinate2>,
+    vertices: Vec<VertexCoord>,
+    edges: Vec<EdgeCoord>,
+    coast: Vec<EdgeId>,
+
+    // Ids of the coordinates in grids sized by the radius, None for coordinates off the board
+    tile_ids: Vec<Option<TileId>>,
+    vertex_ids: Vec<Option<VertexId>>,
+    edge_ids: Vec<Option<EdgeId>>,
+
+    tile_vertices: Vec<[VertexId; 6]>,
+    vertex_tiles: Vec<Vec<TileId>>,
+    vertex_edges: Vec<Vec<EdgeId>>,
+    vertex_neighbors: Vec<Vec<VertexId>>,
+    edge_vertices: Vec<[VertexId; 2]>,
+    edge_tiles: Vec<Vec<TileId>>,
+}
+
+impl Topology {
+
+    /**
+     * Get the topology of a layout, building it the first time the layout is used
+     */
+    pub fn of(layout: Layout) -> &'static Topology {
+        let mut topologies = TOPOLOGIES.lock().unwrap();
+        if let Some(topology) = topologies.iter().find(|e| e.layout == layout) {
+            return topology
+        }
+        let topology: &'static Topology = Box::leak(Box::new(Topology::new(layout)));
+        topologies.push(topology);
+        topology
+    }
+
+    fn new(layout: Layout) -> Topology {
+        let r = layout.get_radius();
+        let tile_size = (r * 2 + 1) as usize;
+        let size = (r * 2 + 3) as usize;
+
+        let mut topology = Topology {
+            layout,
+            tiles: Vec::new(),
+            vertices: Vec::new(),
+            edges: Vec::new(),
+            coast: Vec::new(),
+            tile_ids: vec![None; tile_size * tile_size],
+            vertex_ids: vec![None; size * size * size],
+            edge_ids: vec![None; 3 * size * size],
+            tile_vertices: Vec::new(),
+            vertex_tiles: Vec::new(),
+            vertex_edges: Vec::new(),
+            vertex_neighbors: Vec::new(),
+            edge_vertices: Vec::new(),
+            edge_tiles: Vec::new(),
+        };
+
+        // Number the coordinates
+        for y in -r ..= r {
+            for x in -r ..= r {
+                let coord = Coordinate2::new(x, y);
+                if layout.is_valid_tile(&coord) {
+                    let i = topology.tile_index(&coord).unwrap();
+                    topology.tile_ids[i] = Some(topology.tiles.len());
+                    topology.tiles.push(coord);
+                }
+            }
+        }
+        for x in -r - 1 ..= r + 1 {
+            for y in -r - 1 ..= r + 1 {
+                for z in -r - 1 ..= r + 1 {
+                    match VertexCoord::try_new(x, y, z) {
+                        Some(coord) if layout.is_valid_vertex(&coord) => {
+                            let i = topology.vertex_index(&coord).unwrap();
+                            topology.vertex_ids[i] = Some(topology.vertices.len());
+                            topology.vertices.push(coord);
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        for d in 0 ..= 2 {
+            for y in -r - 1 ..= r + 1 {
+                for z in -r - 1 ..= r + 1 {
+                    let coord = EdgeCoord::new(d, y, z);
+                    if layout.is_valid_edge(&coord) {
+                        let i = topology.edge_index(&coord).unwrap();
+                        topology.edge_ids[i] = Some(topology.edges.len());
+                        topology.edges.push(coord);
+                    }
+                }
+            }
+        }
+
+        // Link them together, every neighbor of a coordinate on the board is found in the
+        // same order as the coordinate functions of the board list them
+        topology.tile_vertices = topology.tiles.iter().map(|e| {
+            Board::get_tile_adjacent_vertex_coords(e).map(|v| topology.get_vertex_id(&v).unwrap())
+        }).collect();
+        topology.vertex_tiles = topology.vertices.iter().map(|e| {
+            Board::get_vertex_adjacent_tile_coords(e).iter().filter_map(|t| topology.get_tile_id(t)).collect()
+        }).collect();
+        topology.vertex_edges = topology.vertices.iter().map(|e| {
+            (0 ..= 2).map(|d| Board::get_prime_pair_vertex(d, e)).filter(|v| {
+                layout.is_valid_vertex(v)
+            }).map(|v| {
+                topology.get_edge_id(&Board::get_edge_between_vertices_coords(e, &v)).unwrap()
+            }).collect()
+        }).collect();
+        topology.edge_vertices = topology.edges.iter().map(|e| {
+            let vertices = Board::get_edge_adjacent_vertex_coords(e);
+            [topology.get_vertex_id(&vertices[0]).unwrap(), topology.get_vertex_id(&vertices[1]).unwrap()]
+        }).collect();
+        topology.vertex_neighbors = (0 .. topology.vertices.len()).map(|v| {
+            topology.vertex_edges[v].iter().map(|e| {
+                let [a, b] = topology.edge_vertices[*e];
+                if a == v { b } else { a }
+            }).collect()
+        }).collect();
+        topology.edge_tiles = topology.edges.iter().map(|e| {
+            Board::get_edge_adjacent_tile_coords(e).iter().filter_map(|t| topology.get_tile_id(t)).collect()
+        }).collect();
+        topology.coast = (0 .. topology.edges.len()).filter(|e| topology.edge_tiles[*e].len() == 1).collect();
+
+        topology
+    }
+
+    /**
+     * Index of a tile in the tile id grid, None if it is outside of the grid
+     */
+    fn tile_index(&self, coord: &Coordinate2) -> Option<usize> {
+        let r = self.layout.get_radius();
+        if !coord.is_valid_tile(r) {
+            return None
+        }
+        let size = (r * 2 + 1) as usize;
+        Some((coord.x + r) as usize * size + (coord.y + r) as usize)
+    }
+
+    /**
+     * Index of a vertex in the vertex id grid, None if it is outside of the grid
+     */
+    fn vertex_index(&self, coord: &VertexCoord) -> Option<usize> {
+        let r = self.layout.get_radius() + 1;
+        if ![coord.x, coord.y, coord.z].iter().all(|e| (-r ..= r).contains(e)) {
+            return None
+        }
+        let size = (r * 2 + 1) as usize;
+        Some(((coord.x + r) as usize * size + (coord.y + r) as usize) * size + (coord.z + r) as usize)
+    }
+
+    /**
+     * Index of an edge in the edge id grid, None if it is outside of the grid
+     */
+    fn edge_index(&self, coord: &EdgeCoord) -> Option<usize> {
+        let r = self.layout.get_radius() + 1;
+        if ![coord.y, coord.z].iter().all(|e| (-r ..= r).contains(e)) {
+            return None
+        }
+        let size = (r * 2 + 1) as usize;
+        Some((coord.axis as usize * size + (coord.y + r) as usize) * size + (coord.z + r) as usize)
+    }
+
+    pub fn get_layout(&self) -> Layout {
+        self.layout
+    }
+
+    /**
+     * Get the id of a tile, or None if it is not on the board
+     */
+    pub fn get_tile_id(&self, coord: &Coordinate2) -> Option<TileId> {
+        self.tile_index(coord).and_then(|i| self.tile_ids[i])
+    }
+
+    /**
+     * Get the id of a vertex, or None if it is not on the board
+     */
+    pub fn get_vertex_id(&self, coord: &VertexCoord) -> Option<VertexId> {
+        self.vertex_index(coord).and_then(|i| self.vertex_ids[i])
+    }
+
+    /**
+     * Get the id of an edge, or None if it is not on the board
+     */
+    pub fn get_edge_id(&self, coord: &EdgeCoord) -> Option<EdgeId> {
+        self.edge_index(coord).and_then(|i| self.edge_ids[i])
+    }
+
+    /**
+     * The tile coordinates, indexed by id
+     */
+    pub fn get_tiles(&self) -> &[Coordinate2] {
+        &self.tiles
+    }
+
+    /**
+     * The vertex coordinates, indexed by id
+     */
+    pub fn get_vertices(&self) -> &[VertexCoord] {
+        &self.vertices
+    }
+
+    /**
+     * The edge coordinates, indexed by id
+     */
+    pub fn get_edges(&self) -> &[EdgeCoord] {
+        &self.edges
+    }
+
+    /**
+     * The edges on the coast of the board, touching exactly one tile
+     */
+    pub fn get_coast_edges(&self) -> &[EdgeId] {
+        &self.coast
+    }
+
+    /**
+     * The 6 vertices around a tile
+     */
+    pub fn get_tile_vertices(&self, tile: TileId) -> &[VertexId; 6] {
+        &self.tile_vertices[tile]
+    }
+
+    /**
+     * The 1 to 3 tiles touching a vertex
+     */
+    pub fn get_vertex_tiles(&self, vertex: VertexId) -> &[TileId] {
+        &self.vertex_tiles[vertex]
+    }
+
+    /**
+     * The 2 or 3 edges leaving a vertex
+     */
+    pub fn get_vertex_edges(&self, vertex: VertexId) -> &[EdgeId] {
+        &self.vertex_edges[vertex]
+    }
+
+    /**
+     * The 2 or 3 vertices one edge away from a vertex, in the order of its edges
+     */
+    pub fn get_vertex_neighbors(&self, vertex: VertexId) -> &[VertexId] {
+        &self.vertex_neighbors[vertex]
+    }
+
+    /**
+     * The 2 vertices at the ends of an edge
+     */
+    pub fn get_edge_vertices(&self, edge: EdgeId) -> &[VertexId; 2] {
+        &self.edge_vertices[edge]
+    }
+
+    /**
+     * The 1 or 2 tiles on either side of an edge
+     */
+    pub fn get_edge_tiles(&self, edge: EdgeId) -> &[TileId] {
+        &self.edge_tiles[edge]
+    }
+
+}
@@ -1,296 +1,353 @@
-use std::{u8, vec};
+use crate::board::{building::{Building, BUILDING_SHIFT, PLAYER_VALUE_MASK}, coordinate::{Coordinate2, EdgeCoord, VertexCoord}, tile::{Resource, Tile}};
 
-use rand::rngs::ThreadRng;
+use super::{devs::{DevCard, DevHand}, hand::{self, Hand}, phase::TurnPhase, trade::{TradeOffer, TradeResponse}, undo::Change, Game, MAX_PLAYERS};
 
-use crate::board::{self, building::{Building, BUILDING_SHIFT, PLAYER_VALUE_MASK}, coordinate::{Coordinate2, Coordinate3}, tile::{Tile, TILE_SHIFT, TILE_VALUE_MASK}, Board};
+// Number of times an agent is asked for a valid discard before the cards are discarded at random
+const MAX_DISCARD_ATTEMPTS: u8 = 3;
 
-use super::{hand::{self, MAX_CARDS}, Game};
-
-#[derive(Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum PlayerAction {
     FirstPlacement,
-    FirstSettlement(Coordinate3),
-    InitialRoadPlacement(Coordinate3),
+    FirstSettlement(VertexCoord),
+    InitialRoadPlacement(EdgeCoord),
     SecondPlacement,
-    SecondSettlement(Coordinate3),
-    Discard,
-    PlaceSettlement(Coordinate3),
-    PlaceCity(Coordinate3), 
-    PlaceRoad(Coordinate3),
+    SecondSettlement(VertexCoord),
+    Discard(Hand), // Provide the cards to discard
+    PlaceSettlement(VertexCoord),
+    PlaceCity(VertexCoord), 
+    PlaceRoad(EdgeCoord),
     RobberMove(Coordinate2),
-    RobberSteal(u8, u8),
-    Port(i8, u8, u8),
+    Rob(u8), // Provide the player to rob, the card is stolen at random
+    RobberSteal(u8, Resource),
+    Port(u8, Resource, u8, Resource), // Provide the amount and resource given, then the amount and resource received
     BuyDev,
     PlayKnight,
     PlayYOP,
     PlayMono,
     PlayRoad,
+    YearOfPlenty(Resource, Resource),
+    Monopoly(Resource),
+    FreeRoad(EdgeCoord),
+    ProposeTrade(TradeOffer), // Provide the offer, the other players answer it in turn order
+    AnswerTrade(TradeResponse), // Provide the answer to the offer of the active player
+    Trade(u8, Hand, Hand), // Provide the other player, the cards given and the cards received
+    Roll,
     Pass,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum GameAction {
     GameStart,
     FirstPlacement(u8), // Provide the player
     SecondPlacement(u8), // Provide the player
     PlacementFinished, // Action just to indicate all the placements are done
     Roll(u8, u8),
+    DrawDev(u8, DevCard), // Provide the player and the card drawn from the deck
     TurnDone,
     Finished,
 }
 
+/**
+ * An action applied to the game, as stored in the game record
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub enum RecordedAction {
+    Game(GameAction),
+    Player(u8, PlayerAction),
+}
+
 impl Game {
 
     /**
-     * Perform a player action, and return a list of possible actions. Returning an
-     * empty vector means there are no more actions and the game should move to the next player
-     * 
-     * Returns 2 values, the vector of actions and a boolean flag indicating if a developmentg card was
+     * Apply the effect of a player action to the game and add it to the record, then move
+     * to the next phase. This makes no decisions, so applying the same actions always
+     * results in the same game.
      */
-    pub fn perform_player_action(&mut self, rng: &mut ThreadRng, player: u8, action: PlayerAction) -> Vec<PlayerAction> {
-        println!("Player {} {:?}", player, action);
-        let mut vec = Vec::new();
-        match action {
-            PlayerAction::FirstPlacement => {
-                // Get the valid first placement spots
-                self.board.get_valid_starting_buiding_coords().into_iter().for_each(|e| {
-                    vec.push(PlayerAction::FirstSettlement(e));
-                });
-            }
-            PlayerAction::FirstSettlement(coord) => {
-                // Set the settlement
-                self.board.set_building(&coord, Building::Settlement, player);
+    pub(crate) fn apply_player_action(&mut self, player: u8, action: &PlayerAction) {
+        self.record.push(RecordedAction::Player(player, action.clone()));
 
-                // Get the available road placements
-                self.board.get_placement_roads_around(&coord).into_iter().for_each(|e| {
-                    vec.push(PlayerAction::InitialRoadPlacement(e));
-                });
-            }
-            PlayerAction::SecondPlacement => {
-                // Get the valid first placement spots
-                self.board.get_valid_starting_buiding_coords().into_iter().for_each(|e| {
-                    vec.push(PlayerAction::SecondSettlement(e));
-                });
+        // Trades agreed to are only on offer until the next action, apart from the
+        // answers still being collected
+        if !matches!(action, PlayerAction::AnswerTrade(_)) {
+            self.trade_actions.clear();
+        }
+
+        match *action {
+            PlayerAction::FirstSettlement(ref coord) => {
+                // Set the settlement
+                self.use_settlement(player);
+                self.place_building(coord, Building::Settlement, player);
+                self.phase = TurnPhase::FirstRoad(player, *coord);
             }
-            PlayerAction::SecondSettlement(coord) => {
+            PlayerAction::SecondSettlement(ref coord) => {
                 // Set the settlement
-                self.board.set_building(&coord, Building::Settlement, player);
-
-                // Get the available road placements
-                self.board.get_placement_roads_around(&coord).into_iter().for_each(|e| {
-                    vec.push(PlayerAction:: InitialRoadPlacement(e));
-                });
-                self.distribute_initial_resources(player, &coord);
+                self.use_settlement(player);
+                self.place_building(coord, Building::Settlement, player);
+                self.distribute_initial_resources(player, coord);
+                self.phase = TurnPhase::SecondRoad(player, *coord);
             }
-            PlayerAction::InitialRoadPlacement(coord) => {
+            PlayerAction::InitialRoadPlacement(ref coord) => {
                 // Set the road
-                self.board.set_road(&coord, player);
-            }
-            PlayerAction::Discard => {
-                let old_hand = self.get_hand(player);
-                let new_hand = self.call_player_discard(player);
-                let old_size = hand::size(old_hand);
-                if old_size - hand::size(new_hand) == old_size / 2 {
-                    self.set_hand(player, new_hand);
-                } else {
-                    // Invalid discard, this can either force a forfeit or require them to pick again.
-                    // For now, get the agent to pick again
-                    vec.push(PlayerAction::Discard);
-                }
+                self.use_road(player);
+                self.place_road(coord, player);
+
+                // The first placements go in ascending player order and the second
+                // placements in reverse, then the first player starts their turn
+                self.phase = match self.phase {
+                    TurnPhase::FirstRoad(_, _) if player == self.player_count => TurnPhase::SecondPlacement(player),
+                    TurnPhase::FirstRoad(_, _) => TurnPhase::FirstPlacement(player + 1),
+                    _ if player == 1 => self.start_turn(1),
+                    _ => TurnPhase::SecondPlacement(player - 1),
+                };
             }
-            PlayerAction::RobberMove(coord) => {
-
-                let mut has_players = false;
-                let mut can_steal: [bool; 4] = [false; 4];
+            PlayerAction::Discard(cards) => {
+                self.give_to_bank(player, cards);
+                self.discards[(player - 1) as usize] = 0;
+                self.phase = self.get_phase_after_discard();
+            }
+            PlayerAction::RobberMove(ref coord) => {
+                // Set the robber position
+                self.place_robber(coord);
 
-                // Get robabble players adjacent to these coordinates
-                Board::get_tile_adjacent_vertex_coords(&coord).into_iter().for_each(|e| {
-                    let p = self.board.get_building(&e) & PLAYER_VALUE_MASK;
-                    if p != 0 && player != p {
-                        let hand = self.get_hand(p);
-                        if hand::size(hand) != 0 {
-                            has_players = true;
-                            can_steal[(p - 1) as usize] = true;
-                        }
-                    }
-                });
-
-                // If there are robbable players, ask the player agent which player they should steal
-                if has_players {
-                    let steal= self.call_player_rob(can_steal);
-                    if can_steal[steal] {
-                        let other_player = (steal + 1) as u8;
-                        let hand = self.get_hand(other_player);
-                        let res = Game::steal(hand, rng);
-                        vec.push(PlayerAction::RobberSteal(other_player, res));
-                    } else {
-                        // Either automatically resign the player, or request the the steal again
-                        // For now, we'll just re-request the robber move
-                        return vec![PlayerAction::RobberMove(coord)];
-                    }
+                // Steal from a player next to the robber, if there is anyone to steal from
+                if self.get_robbable_players(player, coord).contains(&true) {
+                    self.phase = TurnPhase::RobberSteal(player);
+                } else {
+                    self.phase = self.get_phase_after_card(player);
                 }
-
-                // Set the robber position
-                self.board.robber = coord;
             }
             PlayerAction::RobberSteal(other_player, res) => {
                 // Remove the resource from the target player
-                let hand = self.get_hand(other_player);
-                let hand = hand::add(hand, res, -1);
-                self.set_hand(other_player, hand);
+                let card = Hand::of(res, 1);
+                self.set_hand(other_player, self.get_hand(other_player) - card);
 
                 // Update the current player's hand with the stolen resource
-                let hand = self.get_hand(player);
-                let hand = hand::add(hand, res, 1);
-                self.set_hand(player, hand);
-
-                // Get more player actions
-                vec.append(&mut self.get_turn_actions(player));
+                self.set_hand(player, self.get_hand(player) + card);
+                self.phase = self.get_phase_after_card(player);
             }
-            PlayerAction::PlaceRoad(coord) => {
-                // Subtract the Road from the player's hand
-                let hand = self.get_hand(player);
-                let hand = hand::subtract(hand, hand::ROAD_RECIPE);
-                self.set_hand(player, hand);
+            PlayerAction::PlaceRoad(ref coord) => {
+                // Pay for the Road
+                self.give_to_bank(player, hand::ROAD_RECIPE);
 
                 // Set the road
-                self.board.set_road(&coord, player);
-
-                // Get more player actions
-                vec.append(&mut self.get_turn_actions(player));
+                self.use_road(player);
+                self.place_road(coord, player);
+                self.update_longest_road();
             }
-            PlayerAction::PlaceSettlement(coord) => {
-                let hand = self.get_hand(player);
-                let hand = hand::subtract(hand, hand::SETTLE_RECIPE);
-                self.set_hand(player, hand);
+            PlayerAction::PlaceSettlement(ref coord) => {
+                self.give_to_bank(player, hand::SETTLE_RECIPE);
 
-                // Set the settlement
-                self.board.set_building(&coord, Building::Settlement, player);
-  
-                // Get more player actions
-                vec.append(&mut self.get_turn_actions(player));
+                // Set the settlement. This can break another player's road
+                self.use_settlement(player);
+                self.place_building(coord, Building::Settlement, player);
+                self.update_longest_road();
             }
-            PlayerAction::PlaceCity(coord) => {
-                let hand = self.get_hand(player);
-                let hand = hand::subtract(hand, hand::CITY_RECIPE);
-                self.set_hand(player, hand);
+            PlayerAction::PlaceCity(ref coord) => {
+                self.give_to_bank(player, hand::CITY_RECIPE);
 
-                // Set the settlement
-                self.board.set_building(&coord, Building::City, player);
-  
-                // Get more player actions
-                vec.append(&mut self.get_turn_actions(player));
+                // Set the city, the settlement goes back to the supply
+                self.use_city(player);
+                self.place_building(coord, Building::City, player);
+                self.update_longest_road();
+            }
+            PlayerAction::BuyDev => {
+                // The card itself is drawn by the DrawDev game action
+                self.give_to_bank(player, hand::DEV_RECIPE);
             }
             PlayerAction::PlayKnight => {
-                // Move the robber
-                self.get_robbable_tiles().into_iter().for_each(|e| {
-                    vec.push(PlayerAction::RobberMove(e))
-                });
-            }
-            PlayerAction::Port(amount, from, to) => {
-                // Take 4 of the from resources and turn it into 1 of the to resources
-                let hand = self.get_hand(player);
-                let hand = hand::add(hand, from, -amount);
-                let hand = hand::add(hand, to, 1);
-                self.set_hand(player, hand);
-                vec.append(&mut self.get_turn_actions(player));
+                self.remove_dev(player, DevCard::Knight);
+                self.add_played_knight(player);
+                self.dev_played = true;
+                self.phase = TurnPhase::RobberMove(player);
+            }
+            PlayerAction::PlayYOP => {
+                self.remove_dev(player, DevCard::YearOfPlenty);
+                self.dev_played = true;
+                self.phase = TurnPhase::YearOfPlenty(player);
+            }
+            PlayerAction::YearOfPlenty(a, b) => {
+                self.take_from_bank(player, Hand::of(a, 1) + Hand::of(b, 1));
+                self.phase = self.get_phase_after_card(player);
+            }
+            PlayerAction::PlayMono => {
+                self.remove_dev(player, DevCard::Monopoly);
+                self.dev_played = true;
+                self.phase = TurnPhase::Monopoly(player);
+            }
+            PlayerAction::Monopoly(res) => {
+                // Collect every card of the resource from the other players
+                let mut total = 0;
+                for p in 1 ..= self.player_count {
+                    if p != player {
+                        let hand = self.get_hand(p);
+                        self.set_hand(p, hand.with(res, 0));
+                        total += hand[res];
+                    }
+                }
+                self.set_hand(player, self.get_hand(player) + Hand::of(res, total));
+                self.phase = self.get_phase_after_card(player);
+            }
+            PlayerAction::PlayRoad => {
+                self.remove_dev(player, DevCard::RoadBuilding);
+                self.dev_played = true;
+                self.free_roads = 2;
+                self.phase = TurnPhase::RoadBuilding(player);
+            }
+            PlayerAction::FreeRoad(ref coord) => {
+                self.use_road(player);
+                self.place_road(coord, player);
+                self.update_longest_road();
+                self.free_roads -= 1;
+
+                // The second road is lost if there is no spot or no road left for it
+                if self.get_legal_road_locations(player).next().is_none() {
+                    self.free_roads = 0;
+                }
+                if self.free_roads == 0 {
+                    self.phase = self.get_phase_after_card(player);
+                }
+            }
+            PlayerAction::Port(give, from, receive, to) => {
+                // Trade the from resources with the bank for the to resources
+                self.give_to_bank(player, Hand::of(from, give.into()));
+                self.take_from_bank(player, Hand::of(to, receive.into()));
             },
-            _=> {}
-        }
-
-        // Check if a player has won. If so, go back to the game 
-        if self.has_winner() {
-            return Vec::new();
+            PlayerAction::ProposeTrade(offer) => {
+                self.phase = TurnPhase::TradeAnswer(self.next_player(player), offer);
+            }
+            PlayerAction::AnswerTrade(response) => {
+                self.answer_trade(player, response);
+            }
+            PlayerAction::Trade(other, give, receive) => {
+                self.apply_trade(player, other, &TradeOffer::new(give, receive));
+            }
+            PlayerAction::Roll => {
+                self.phase = TurnPhase::Roll(player);
+            }
+            PlayerAction::Pass => {
+                self.phase = match self.get_phase_after_pass() {
+                    TurnPhase::PreRoll(next) => self.start_turn(next),
+                    phase => phase,
+                };
+            }
+            PlayerAction::FirstPlacement
+            | PlayerAction::SecondPlacement
+            | PlayerAction::Rob(_) => {}
         }
 
-        vec
+        self.check_winner();
     }
 
     /**
-     * Perform a game action and return the next action. Game actions require no decisions,
-     * and hence only one must be returned.
+     * Apply the effect of a game action to the game and add it to the record, then move
+     * to the next phase
      */
-    pub fn perform_game_action(&mut self, rng: &mut ThreadRng, action: GameAction) -> GameAction {
-        match action {
-            GameAction::GameStart => GameAction::FirstPlacement(1),
+    pub(crate) fn apply_game_action(&mut self, action: &GameAction) {
+        self.record.push(RecordedAction::Game(action.clone()));
+        match *action {
+            GameAction::GameStart => {
+                self.phase = TurnPhase::FirstPlacement(1);
+            }
             GameAction::FirstPlacement(player) => {
-                let actions = self.perform_player_action(rng, player, PlayerAction::FirstPlacement);
-                self.call_player(rng, player, actions);
-                if player == self.player_count {
-                    GameAction::SecondPlacement(player)
-                } else {
-                    GameAction::FirstPlacement(player + 1)
-                }
+                self.phase = TurnPhase::FirstSettlement(player);
             }
             GameAction::SecondPlacement(player) => {
-                let actions = self.perform_player_action(rng, player, PlayerAction::SecondPlacement);
-                self.call_player(rng, player, actions);
-                if player == 1 {
-                    GameAction::PlacementFinished
-                } else {
-                    GameAction::SecondPlacement(player - 1)
-                }
+                self.phase = TurnPhase::SecondSettlement(player);
             }
             GameAction::Roll(player, value) => {
-                println!("Roll {} : {}", value, player);
+                self.turn_player = player;
+                self.has_rolled = true;
                 if value == 7 {
-                    
-                    // Check if a player must discard cards
-                    for p in 1 ..= self.player_count {
-                        let hand = self.get_hand(p);
-                        if hand::size(hand) > self.max_cards.into() {
-                            self.perform_player_action(rng, p, PlayerAction::Discard);
-                        }
-                    }
-            
-                    // Possible robber action for current player
-                    let actions = self.get_robbable_tiles().into_iter().map(|e| {
-                        PlayerAction::RobberMove(e)
-                    }).collect();
-                    self.call_player(rng, player, actions);
+                    // Players with too many cards discard before the robber is moved
+                    self.phase = self.start_discards();
                 } else {
                     self.distribute_resources_for_roll(value);
+                    self.phase = TurnPhase::Main(player);
                 }
+            }
+            GameAction::DrawDev(player, card) => {
+                self.draw_dev(player, card);
+
+                // Cards bought on the player's turn are played on a later turn
+                if player == self.turn_player {
+                    self.bought_devs += DevHand::of(card, 1);
+                }
+            }
+            GameAction::Finished => {
+                self.phase = TurnPhase::Finished;
+            }
+            GameAction::PlacementFinished | GameAction::TurnDone => {}
+        }
+
+        self.check_winner();
+    }
+
+    /**
+     * Finish the game once the player whose turn it is has reached the victory point
+     * target. Players can only win on their own turn, so a player who reaches the target
+     * on another player's turn wins at the start of their next turn.
+     */
+    fn check_winner(&mut self) {
+        let player = self.turn_player;
+        if player == 0 || matches!(self.phase, TurnPhase::SpecialBuilding(_)) {
+            return
+        }
+        if self.get_points()[(player - 1) as usize] >= self.vp_target {
+            self.phase = TurnPhase::Finished;
+        }
+    }
 
-                let actions = self.get_turn_actions(player);
-                self.call_player(rng, player, actions);
-            
-                GameAction::TurnDone
+    /**
+     * Apply a recorded action to the game
+     */
+    pub fn apply_recorded_action(&mut self, action: &RecordedAction) {
+        match action {
+            RecordedAction::Game(action) => self.apply_game_action(action),
+            RecordedAction::Player(player, action) => self.apply_player_action(*player, action),
+        }
+    }
+
+    /**
+     * Ask the agent for a discard until it provides a valid one, and return the cards
+     * to discard. The hand the agent keeps must be part of its hand and hold all
+     * but the cards to discard. An agent that fails too many times forfeits the choice,
+     * and the cards are discarded at random.
+     */
+    pub(super) fn get_player_discard(&mut self, player: u8) -> Hand {
+        let hand = self.get_hand(player);
+        let count = self.get_discard_count(player);
+        for _ in 0 .. MAX_DISCARD_ATTEMPTS {
+            let new_hand = self.call_player_discard(player);
+            if hand.has(new_hand) && new_hand.size() + count == hand.size() {
+                return hand - new_hand;
             }
-            _ => GameAction::Finished,
         }
+
+        let mut cards = Hand::EMPTY;
+        for _ in 0 .. count {
+            let res = self.steal(hand - cards);
+            cards += Hand::of(res, 1);
+        }
+        cards
     }
 
     /**
      * Handle distributing the inital resource around a settlement at the specific vertex
      * coordinate.
      */
-    fn distribute_initial_resources(&mut self, player: u8, coord: &Coordinate3) {
-        let robber: crate::board::coordinate::Coordinate2 = self.board.robber.clone();
-        Board::get_vertex_adjacent_tile_coords(coord).into_iter().filter(|e|
-            e.is_valid_tile() && robber != *e
-        ).for_each(|e| {
-            let (tile, _) = Tile::from_u8(self.board.get_tile(&e));
-            let hand = self.get_hand(player);
-            let new_hand = match tile {
-                Tile::Brick => {
-                    hand::add_brick(hand, 1)
-                },
-                Tile::Lumber => {
-                    hand::add_lumber(hand, 1)
-                },
-                Tile::Sheep => {
-                    hand::add_sheep(hand, 1)
-                },
-                Tile::Ore => {
-                    hand::add_ore(hand, 1)
-                },
-                Tile::Wheat => {
-                    hand::add_wheat(hand, 1)
-                },
-                _ => hand,
+    fn distribute_initial_resources(&mut self, player: u8, coord: &VertexCoord) {
+        let topology = self.board.get_topology();
+        let robber = topology.get_tile_id(&self.board.robber);
+        let vertex = topology.get_vertex_id(coord).unwrap();
+        topology.get_vertex_tiles(vertex).iter().filter(|e| robber != Some(**e)).for_each(|e| {
+            let (tile, _) = Tile::from_u8(self.board.get_tile_by_id(*e));
+            let cards = match tile.as_resource() {
+                Some(res) => Hand::of(res, 1),
+                None => Hand::EMPTY,
             };
-            self.set_hand(player, new_hand);
+            if self.bank.has(cards) {
+                self.take_from_bank(player, cards);
+            }
         });
     }
 
@@ -298,99 +355,140 @@ impl Game {
      * Handle distributing resources after a roll
      */
     fn distribute_resources_for_roll(&mut self, value: u8) {
-        let mut ops: Vec<(u8, u8, u8)> = Vec::new();
-        let mut totals = [
-            self.get_resource_card_count(1),
-            self.get_resource_card_count(2),
-            self.get_resource_card_count(3),
-            self.get_resource_card_count(4),
-            self.get_resource_card_count(5),
-        ];
-
-        // Generate a list of possible operations. This allows us to make sure
-        // there are enough resources to distribute.
-        Board::get_tile_coords().into_iter().for_each(|e| {
-            let tile= self.board.get_tile(&e);
-            if e != self.board.robber && tile & TILE_VALUE_MASK == value {
-                // Get the numeric Tile ID
-                let tile = tile >> TILE_SHIFT;
-                for v in Board::get_tile_adjacent_vertex_coords(&e) {
-                    let building = self.board.get_building(&v);
-                    if building != 0 {
-                        let player = building & PLAYER_VALUE_MASK;
-                        let building = building >> BUILDING_SHIFT;
-
-                        // Update the totals in the building
-                        totals[(tile - 1) as usize] += building as u32;
-                        ops.push((player, tile, building));
-                    }
+        // The cards of each resource owed to each player
+        let mut owed = [[0; MAX_PLAYERS]; 5];
+
+        // Count what is owed first, so the bank can check it has enough of each resource
+        let topology = self.board.get_topology();
+        let robber = topology.get_tile_id(&self.board.robber);
+        for e in 0 .. topology.get_tiles().len() {
+            let (tile, tile_value) = Tile::from_u8(self.board.get_tile_by_id(e));
+            let res = match tile.as_resource() {
+                Some(res) if robber != Some(e) && tile_value == value => res,
+                _ => continue,
+            };
+            for v in topology.get_tile_vertices(e) {
+                let building = self.board.get_building_by_id(*v);
+                if building != 0 {
+                    let player = building & PLAYER_VALUE_MASK;
+                    let building = building >> BUILDING_SHIFT;
+
+                    // A settlement is owed 1 card and a city 2
+                    owed[res as usize][(player - 1) as usize] += building as u32;
                 }
             }
-        });
+        }
+
+        for res in Resource::ALL {
+            let players = self.player_count as usize;
+            self.pay_resource(res, &owed[res as usize][.. players]);
+        }
+    }
 
-        // Apply the operations if there are enough resources to do it
-        ops.into_iter().for_each(|(player, tile, building)| {
-            if totals[(tile - 1) as usize] <= MAX_CARDS {
-                let hand = self.get_hand(player);
-                let hand = hand::add(hand, tile, building as i8);
-                self.set_hand(player, hand);
+    /**
+     * Get the players with cards next to a tile, other than the player moving the robber.
+     *
+     * Typically we index players starting from 1, in this case player 1 is 0.
+     */
+    pub(super) fn get_robbable_players(&self, player: u8, coord: &Coordinate2) -> [bool; MAX_PLAYERS] {
+        let mut can_steal: [bool; MAX_PLAYERS] = [false; MAX_PLAYERS];
+        let topology = self.board.get_topology();
+        let tile = topology.get_tile_id(coord).unwrap();
+        topology.get_tile_vertices(tile).iter().for_each(|e| {
+            let p = self.board.get_building_by_id(*e) & PLAYER_VALUE_MASK;
+            if p != 0 && player != p && !self.get_hand(p).is_empty() {
+                can_steal[(p - 1) as usize] = true;
             }
         });
+        can_steal
     }
 
     /**
-     * Get the sum of all resource cards by a specific type 
-     * which are currently in player hands
+     * Get all the robbable tiles (excludes the current tile the robber is on)
      */
-    fn get_resource_card_count(&self, res: u8) -> u32 {
-        let mut sum: u32 = 0;
-        for p in 0 ..= 3 {
-            let hand = self.hands[p];
-            sum += hand::count(hand, res);
-        }
-        sum
+    pub(super) fn get_robbable_tiles(&self) -> impl Iterator<Item = Coordinate2> + '_ {
+        self.board.get_tile_coords().iter().filter(|e| {
+            self.board.robber != **e
+        }).cloned()
     }
 
     /**
-     * Get all the robbable tiles (excludes the current tile the robber is on)
+     * Update a player's development cards
+     */
+    pub(super) fn set_devs(&mut self, player: u8, devs: DevHand) {
+        self.log(Change::Devs(player, self.get_devs(player)));
+        self.devs[(player - 1) as usize] = devs;
+    }
+
+    /**
+     * Take a development card out of a player's hand to play it
+     */
+    fn remove_dev(&mut self, player: u8, card: DevCard) {
+        self.set_devs(player, self.get_devs(player) - DevHand::of(card, 1));
+    }
+
+    /**
+     * Get the pairs of resources that can be taken from the bank with a year of plenty
      */
-    fn get_robbable_tiles(&self) -> Vec<Coordinate2> {
-        Board::get_tile_coords().into_iter().filter(|e| {
-            &self.board.robber != e
-        }).collect()
+    pub(super) fn get_year_of_plenty_actions(&self) -> Vec<PlayerAction> {
+        let mut vec = Vec::new();
+        for (i, a) in Resource::ALL.into_iter().enumerate() {
+            for b in Resource::ALL[i ..].iter().copied() {
+                let needed = if a == b { 2 } else { 1 };
+                if self.bank.count(a) >= needed && self.bank.count(b) >= needed {
+                    vec.push(PlayerAction::YearOfPlenty(a, b));
+                }
+            }
+        }
+        vec
     }
 
     /**
-     * Helper function to generate all the legal turn actions
+     * Helper function to generate all the legal turn actions. During the Special Building
+     * Phase the player may only build or buy development cards.
      */
     pub fn get_turn_actions(&self, player: u8) -> Vec<PlayerAction> {
-        // TODO - add dev support, only one dev card can be player a turn
         let mut vec = Vec::new();
 
         let hand = self.get_hand(player);
 
         // Road Building
-        if hand::has(hand, hand::ROAD_RECIPE) {
-            println!("{:?}", self.get_legal_road_locations(player).len());
-            self.get_legal_road_locations(player).into_iter().for_each(|e| {
+        if hand.has(hand::ROAD_RECIPE) {
+            self.get_legal_road_locations(player).for_each(|e| {
                 vec.push(PlayerAction::PlaceRoad(e))
             });
         }
 
         // Settlement
-        if hand::has(hand, hand::SETTLE_RECIPE) {
-            self.get_legal_settle_locations(player).into_iter().for_each(|e| {
+        if hand.has(hand::SETTLE_RECIPE) {
+            self.get_legal_settle_locations(player).for_each(|e| {
                 vec.push(PlayerAction::PlaceSettlement(e));
             });
         }
 
         // City
-        if hand::has(hand, hand::CITY_RECIPE) {
-            self.get_legal_city_locations(player).into_iter().for_each(|e| {
+        if hand.has(hand::CITY_RECIPE) {
+            self.get_legal_city_locations(player).for_each(|e| {
                 vec.push(PlayerAction::PlaceCity(e));
             });
         }
 
+        // Buy a development card
+        if hand.has(hand::DEV_RECIPE) && self.bank.get_dev_count() > 0 {
+            vec.push(PlayerAction::BuyDev);
+        }
+
+        if let TurnPhase::SpecialBuilding(_) = self.phase {
+            vec.push(PlayerAction::Pass);
+            return vec
+        }
+
+        // Play a development card
+        vec.append(&mut self.get_dev_actions(player));
+
+        // Trade with the other players
+        vec.append(&mut self.get_trade_offer_actions(player));
+
         // Ports
         vec.append(&mut self.get_port_actions(player));
 
@@ -401,101 +499,245 @@ impl Game {
     }
 
     /**
-     * Get all the actions
+     * Get the development cards a player can play. Only one card may be played a turn,
+     * and a card bought this turn can not be played until a later turn.
      */
-    fn get_port_actions(&self, player: u8) -> Vec<PlayerAction> {
+    pub(super) fn get_dev_actions(&self, player: u8) -> Vec<PlayerAction> {
         let mut vec = Vec::new();
-        let mut ports = [false; 6];
-        board::PORT_LOCATIONS.into_iter().enumerate().for_each(|(i, (x, y, z))| {
-            Board::get_edge_adjacent_vertex_coords(&Coordinate3::new(x, y, z))
-                .into_iter()
-                .for_each(|e| {
-                    if self.board.get_building(&e) & PLAYER_VALUE_MASK == player {
-                        ports[self.board.ports[i] as usize] = true;
-                    }
-                });
-        });
+        if self.check_dev(player, DevCard::Knight).is_ok() {
+            vec.push(PlayerAction::PlayKnight);
+        }
+        if self.check_dev(player, DevCard::RoadBuilding).is_ok() && self.get_legal_road_locations(player).next().is_some() {
+            vec.push(PlayerAction::PlayRoad);
+        }
+        if self.check_dev(player, DevCard::YearOfPlenty).is_ok() && !self.get_year_of_plenty_actions().is_empty() {
+            vec.push(PlayerAction::PlayYOP);
+        }
+        if self.check_dev(player, DevCard::Monopoly).is_ok() {
+            vec.push(PlayerAction::PlayMono);
+        }
+        vec
+    }
 
-        let hand: u32 = self.get_hand(player);
-        for res in 1 ..= 5 {
-            let count = hand::count(hand, res);
-            if count >= 4 {
-                for to in 1 ..= 5 {
-                    if res != to {
-                        if ports[res as usize] {
-                            if count >= 2 {
-                                vec.push(PlayerAction::Port(2, res, to));
-                            }
-                        } else if ports[0] {
-                            if count >= 3 {
-                                vec.push(PlayerAction::Port(3, res, to));
-                            }
-                        } else {
-                            if count >= 4 {
-                                vec.push(PlayerAction::Port(4, res, to));
-                            }
-                        }
+    /**
+     * Get all the trades with the bank at the player's best rate for each resource,
+     * including trades of several cards at once
+     */
+    fn get_port_actions(&self, player: u8) -> Vec<PlayerAction> {
+        let mut vec = Vec::new();
+        let hand = self.get_hand(player);
+        for from in Resource::ALL {
+            let rate = self.board.get_trade_rate(player, from);
+            let count = hand[from] as u8;
+            for receive in 1 ..= count / rate {
+                for to in Resource::ALL {
+                    if from != to && self.bank.count(to) >= receive as u32 {
+                        vec.push(PlayerAction::Port(receive * rate, from, receive, to));
                     }
                 }
             }
         }
-
         vec
     }
 
     /**
      * Get the locations that a player can build
      */
-    fn get_legal_settle_locations(&self, player: u8) -> Vec<Coordinate3> {
-        // Get the road locations that belong to the 
-        self.board.get_valid_starting_buiding_coords().into_iter().filter(|e| {
-            // Make sure the player has a road adjacent to this vertex
-            for e in Board::get_vertex_adjacent_edge_coords(e) {
-                let road = self.board.get_road(e.x, &Coordinate2::new(e.y, e.z));
-                if road == player {
-                    return true
-                }
-            }
-            false
-        }).collect()
+    fn get_legal_settle_locations(&self, player: u8) -> impl Iterator<Item = VertexCoord> + '_ {
+        // No settlements left to build with
+        let vertices = if self.get_pieces(player).settlements == 0 { 0 } else { self.board.buildings.len() };
+
+        (0 .. vertices).filter(move |v| {
+            self.check_settlement_at(player, *v).is_ok()
+        }).map(|v| self.board.get_vertex_coords()[v])
     }
 
     /**
      * Get the locations that a player can build
      */
-    fn get_legal_road_locations(&self, player: u8) -> Vec<Coordinate3> {
-        // Get the road locations that belong to the 
-        Board::get_edge_coords().into_iter().filter(|e| {
-            let road = self.board.get_road(e.x, &Coordinate2::new(e.y, e.z));
-
-            // If this road is already built, you can't build here
-            if road != 0 {
-                return false
-            }
-
-            // If not, check to see if any of the adjacent roads is the player's
-            for v in Board::get_edge_adjacent_vertex_coords(e) {
-                for e in Board::get_vertex_adjacent_edge_coords(&v) {
-                    // Check to see if any of the adjacent roads are available
-                    let road = self.board.get_road(e.x, &Coordinate2::new(e.y, e.z));
-                    if road == player {
-                        return true
-                    }
-                }
-            }
+    pub(super) fn get_legal_road_locations(&self, player: u8) -> impl Iterator<Item = EdgeCoord> + '_ {
+        // No roads left to build with
+        let edges = if self.get_pieces(player).roads == 0 { 0 } else { self.board.roads.len() };
 
-            false
-        }).collect()
+        (0 .. edges).filter(move |e| {
+            self.check_road_at(player, *e).is_ok()
+        }).map(|e| self.board.get_edge_coords()[e])
     }
 
     /**
      * Get the legal city locations for a player
      */
-    fn get_legal_city_locations(&self, player: u8) -> Vec<Coordinate3> {
-        Board::get_vertex_coords().into_iter().filter(|e| {
-            let building = self.board.get_building(e);
-            building & PLAYER_VALUE_MASK == player && (building >> BUILDING_SHIFT) == 1
-        }).collect()
+    fn get_legal_city_locations(&self, player: u8) -> impl Iterator<Item = VertexCoord> + '_ {
+        // No cities left to build with
+        let vertices = if self.get_pieces(player).cities == 0 { 0 } else { self.board.buildings.len() };
+
+        (0 .. vertices).filter(move |v| {
+            self.check_city_at(player, *v).is_ok()
+        }).map(|v| self.board.get_vertex_coords()[v])
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use crate::board::tile::Resource;
+    use crate::game::{
+        agent::PlayerAgent, devs::{DevCard, DevHand}, hand::Hand, phase::TurnPhase, trade::{TradeOffer, TradeResponse},
+        validate::IllegalAction, Game, MAX_PLAYERS,
+    };
+
+    use super::{GameAction, PlayerAction, MAX_DISCARD_ATTEMPTS};
+
+    /**
+     * Agent that keeps each of a list of hands in turn when asked to discard, and counts
+     * how often it was asked
+     */
+    struct DiscardAgent {
+        kept: Vec<Hand>,
+        calls: Rc<Cell<usize>>,
+    }
+
+    impl PlayerAgent for DiscardAgent {
+        fn choose_action(&mut self, _game: &Game, _player: u8, _actions: &[PlayerAction]) -> usize {
+            unreachable!()
+        }
+
+        fn choose_discard(&mut self, _game: &Game, _player: u8, _count: u32) -> Hand {
+            let i = self.calls.get();
+            self.calls.set(i + 1);
+            self.kept[i.min(self.kept.len() - 1)]
+        }
+
+        fn choose_robbery(&mut self, _game: &Game, _player: u8, _options: [bool; MAX_PLAYERS]) -> usize {
+            unreachable!()
+        }
+
+        fn respond_to_trade(&mut self, _game: &Game, _player: u8, _from: u8, _offer: &TradeOffer) -> TradeResponse {
+            unreachable!()
+        }
+    }
+
+    /**
+     * A 3 player game where the first player rolled a 7 with the players holding some hands
+     */
+    fn game_after_seven(hands: [Hand; 3]) -> Game {
+        let mut game = Game::random_from_seed(3, "seven".to_string());
+        for (i, hand) in hands.into_iter().enumerate() {
+            game.set_hand(i as u8 + 1, hand);
+        }
+        game.apply_game_action(&GameAction::Roll(1, 7));
+        game
+    }
+
+    fn set_discard_agent(game: &mut Game, player: u8, kept: Vec<Hand>) -> Rc<Cell<usize>> {
+        let calls = Rc::new(Cell::new(0));
+        game.set_agent(player, Box::new(DiscardAgent { kept, calls: calls.clone() }));
+        calls
+    }
+
+    /**
+     * A game on the first player's turn, after the roll
+     */
+    fn game_in_main(player_count: u8) -> Game {
+        let mut game = Game::random_from_seed(player_count, "win".to_string());
+        game.apply_game_action(&GameAction::Roll(1, 8));
+        assert_eq!(*game.get_phase(), TurnPhase::Main(1));
+        game
+    }
+
+    #[test]
+    fn invalid_discard_is_asked_again() {
+        let hand = Hand::of(Resource::Brick, 4) + Hand::of(Resource::Wheat, 5);
+        let kept = Hand::of(Resource::Brick, 1) + Hand::of(Resource::Wheat, 4);
+        let mut game = game_after_seven([hand, Hand::EMPTY, Hand::EMPTY]);
+        assert_eq!(game.get_discard_count(1), 4);
+
+        // The first answer keeps too many cards, the second keeps cards not in the hand
+        let wrong = Hand::of(Resource::Ore, 5);
+        let calls = set_discard_agent(&mut game, 1, vec![hand, wrong, kept]);
+        assert_eq!(game.get_player_discard(1), hand - kept);
+        assert_eq!(calls.get(), 3);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn discard_is_forfeited_after_too_many_invalid_answers() {
+        let hand = Hand::of(Resource::Brick, 4) + Hand::of(Resource::Wheat, 4);
+        let mut game = game_after_seven([hand, Hand::EMPTY, Hand::EMPTY]);
+        let calls = set_discard_agent(&mut game, 1, vec![hand]);
+
+        let cards = game.get_player_discard(1);
+        assert_eq!(calls.get(), MAX_DISCARD_ATTEMPTS as usize);
+        assert_eq!(cards.size(), 4);
+        assert!(hand.has(cards));
+
+        game.apply_as(1, PlayerAction::Discard(cards)).unwrap();
+        assert_eq!(game.get_hand(1).size(), 4);
+        assert_eq!(*game.get_phase(), TurnPhase::RobberMove(1));
+    }
+
+    #[test]
+    fn players_discard_in_any_order_before_the_robber_moves() {
+        let first = Hand::of(Resource::Ore, 8);
+        let second = Hand::of(Resource::Sheep, 5) + Hand::of(Resource::Lumber, 5);
+        let mut game = game_after_seven([first, second, Hand::of(Resource::Brick, 7)]);
+        assert_eq!(*game.get_phase(), TurnPhase::Discard);
+        assert_eq!(game.get_discard_count(3), 0);
+
+        let discard = Hand::of(Resource::Sheep, 3) + Hand::of(Resource::Lumber, 2);
+        game.apply_as(2, PlayerAction::Discard(discard)).unwrap();
+        assert_eq!(game.get_hand(2), second - discard);
+        assert_eq!(*game.get_phase(), TurnPhase::Discard);
+        assert_eq!(game.get_current_player(), 1);
+
+        assert_eq!(game.apply_as(2, PlayerAction::Discard(discard)), Err(IllegalAction::WrongPlayer));
+        assert_eq!(game.apply_as(3, PlayerAction::Discard(Hand::of(Resource::Brick, 3))), Err(IllegalAction::WrongPlayer));
+        assert_eq!(game.apply_as(1, PlayerAction::Discard(Hand::of(Resource::Ore, 3))), Err(IllegalAction::InvalidDiscard));
+        assert_eq!(game.apply_as(1, PlayerAction::Discard(Hand::of(Resource::Wheat, 4))), Err(IllegalAction::InsufficientResources));
+
+        game.apply_as(1, PlayerAction::Discard(Hand::of(Resource::Ore, 4))).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::RobberMove(1));
+    }
+
+    #[test]
+    fn win_only_on_own_turn() {
+        let mut game = game_in_main(2);
+        game.set_vp_target(3);
+
+        // The second player goes past the target on the first player's turn
+        game.set_devs(2, DevHand::of(DevCard::VictoryPoint, 4));
+        game.set_hand(1, Hand::of(Resource::Brick, 4));
+        game.apply(PlayerAction::Port(4, Resource::Brick, 1, Resource::Ore)).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::Main(1));
+        assert!(!game.has_winner());
+
+        // And wins once their turn starts
+        game.apply(PlayerAction::Pass).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::Finished);
+        assert_eq!(game.get_winner(), 2);
+
+        let result = game.start();
+        assert_eq!(result.winner, 2);
+        assert_eq!(result.points, vec![0, 4]);
+        assert_eq!(result.turns, game.get_turn_count());
+    }
+
+    #[test]
+    fn win_past_the_target() {
+        let mut game = game_in_main(3);
+        game.set_vp_target(6);
+        game.set_hand(1, Hand::of(Resource::Brick, 8));
+        game.set_devs(1, DevHand::of(DevCard::VictoryPoint, 5));
+        game.apply(PlayerAction::Port(4, Resource::Brick, 1, Resource::Ore)).unwrap();
+        assert!(!game.has_winner());
+
+        // Going from 5 to 7 points still wins
+        game.set_devs(1, DevHand::of(DevCard::VictoryPoint, 7));
+        game.apply(PlayerAction::Port(4, Resource::Brick, 1, Resource::Ore)).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::Finished);
+        assert_eq!(game.get_winner(), 1);
+        assert_eq!(game.get_points()[0], 7);
+    }
+
+}
@@ -0,0 +1,108 @@
+use rand::{seq::{IteratorRandom, SliceRandom}, Rng};
+use rand_pcg::Pcg64;
+
+use crate::board::tile::Resource;
+
+use super::{action::PlayerAction, hand::Hand, trade::{TradeOffer, TradeResponse}, Game, MAX_PLAYERS};
+
+/**
+ * A player agent makes every decision for a single seat at the table. The engine
+ * generates the legal options and the agent picks from them, so bots and human
+ * front-ends can be plugged in without changing the game loop.
+ */
+pub trait PlayerAgent {
+
+    /**
+     * Select the action to play from the list of legal actions.
+     *
+     * Return the index of the selected action in the actions list.
+     */
+    fn choose_action(&mut self, game: &Game, player: u8, actions: &[PlayerAction]) -> usize;
+
+    /**
+     * Select which cards to discard after a "7 out". The player must discard
+     * `count` cards. Every player who must discard is asked before anyone discards.
+     *
+     * Return the hand of the player after the cards have been discarded.
+     * An invalid hand is asked for again, and after a few attempts the cards are
+     * discarded at random.
+     */
+    fn choose_discard(&mut self, game: &Game, player: u8, count: u32) -> Hand;
+
+    /**
+     * Select which player to rob after moving the robber.
+     *
+     * Return the index of which player to rob from the options list.
+     * This must be a valid player, where the value of options[i] == true.
+     * An invalid player is asked for again, and after a few attempts a player
+     * is robbed at random.
+     *
+     * Typically we index players starting from 1, in this case player 1 is 0.
+     */
+    fn choose_robbery(&mut self, game: &Game, player: u8, options: [bool; MAX_PLAYERS]) -> usize;
+
+    /**
+     * Answer a trade offer from the active player. A counter offer is a different
+     * trade, from the point of view of the active player. Trades are offered with
+     * the `ProposeTrade` actions, and an answer the players can not pay for is taken
+     * as a rejection.
+     */
+    fn respond_to_trade(&mut self, game: &Game, player: u8, from: u8, offer: &TradeOffer) -> TradeResponse;
+
+}
+
+/**
+ * Agent that picks every decision at random
+ */
+pub struct RandomAgent {
+    rng: Pcg64,
+}
+
+impl RandomAgent {
+
+    pub fn new(rng: Pcg64) -> Self {
+        RandomAgent { rng }
+    }
+
+}
+
+impl PlayerAgent for RandomAgent {
+
+    fn choose_action(&mut self, _game: &Game, _player: u8, actions: &[PlayerAction]) -> usize {
+        self.rng.gen_range(0 .. actions.len())
+    }
+
+    fn choose_discard(&mut self, game: &Game, player: u8, count: u32) -> Hand {
+        let mut hand = game.get_hand(player);
+        let mut discard_count = count;
+        while discard_count > 0 {
+            let res = *Resource::ALL.choose(&mut self.rng).unwrap();
+            if hand[res] > 0 {
+                discard_count -= 1;
+                hand -= Hand::of(res, 1);
+            }
+        }
+        hand
+    }
+
+    fn choose_robbery(&mut self, _game: &Game, _player: u8, options: [bool; MAX_PLAYERS]) -> usize {
+        let players = options.into_iter()
+            .enumerate()
+            .filter(|(_, b) | { *b }).map(|(i, _)| { i })
+            .collect::<Vec<usize>>();
+        *players.choose(&mut self.rng).unwrap()
+    }
+
+    fn respond_to_trade(&mut self, _game: &Game, _player: u8, _from: u8, offer: &TradeOffer) -> TradeResponse {
+        match self.rng.gen_range(0 .. 3) {
+            0 => TradeResponse::Accept,
+            // Ask for one more card of a resource offered
+            1 => {
+                let res = offer.give.cards().choose(&mut self.rng).unwrap();
+                TradeResponse::Counter(TradeOffer::new(offer.give + Hand::of(res, 1), offer.receive))
+            }
+            _ => TradeResponse::Reject,
+        }
+    }
+
+}
@@ -0,0 +1,194 @@
+use super::{undo::Change, Game};
+
+// Minimum number of road segments to hold the longest road
+pub const MIN_LONGEST_ROAD: u8 = 5;
+
+// Minimum number of played knights to hold the largest army
+pub const MIN_LARGEST_ARMY: u8 = 3;
+
+impl Game {
+
+    /**
+     * Get the length of a player's longest road
+     */
+    pub fn get_road_length(&self, player: u8) -> u8 {
+        self.board.get_longest_road(player)
+    }
+
+    /**
+     * Re-evaluate who holds the longest road. The holder keeps the card on a tie,
+     * and another player only takes it by strictly exceeding the holder. If the
+     * holder's road is broken and the new longest road is tied (or shorter than
+     * the minimum), the card is returned to the bank.
+     */
+    pub(super) fn update_longest_road(&mut self) {
+        let lengths: Vec<u8> = (1 ..= self.player_count).map(|p| {
+            self.get_road_length(p)
+        }).collect();
+        let longest = *lengths.iter().max().unwrap();
+
+        let holder = self.longest_road_player;
+        if holder != 0 && lengths[(holder - 1) as usize] == longest && longest >= MIN_LONGEST_ROAD {
+            return;
+        }
+
+        let leaders: Vec<u8> = (1 ..= self.player_count).filter(|p| {
+            lengths[(p - 1) as usize] == longest
+        }).collect();
+
+        self.longest_road_player = if longest >= MIN_LONGEST_ROAD && leaders.len() == 1 {
+            leaders[0]
+        } else {
+            0
+        };
+    }
+
+    /**
+     * Get the number of knights a player has played
+     */
+    pub fn get_army_size(&self, player: u8) -> u8 {
+        self.played_knights[(player - 1) as usize]
+    }
+
+    /**
+     * Count a played knight towards a player's army, and award the largest army
+     * if the player now strictly exceeds the current holder
+     */
+    pub(super) fn add_played_knight(&mut self, player: u8) {
+        self.log(Change::PlayedKnights(player, self.get_army_size(player)));
+        self.played_knights[(player - 1) as usize] += 1;
+
+        let army = self.get_army_size(player);
+        let holder = self.largest_army_player;
+        if army >= MIN_LARGEST_ARMY && (holder == 0 || army > self.get_army_size(holder)) {
+            self.largest_army_player = player;
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{building::Building, topology::{Topology, VertexId}};
+    use crate::game::Game;
+
+    /**
+     * Build a road of a number of segments for a player, away from the vertices already
+     * used by other roads, and return the vertices along it
+     */
+    fn build_road(game: &mut Game, player: u8, length: usize, used: &mut Vec<VertexId>) -> Vec<VertexId> {
+        let topology = game.board.get_topology();
+        let path = (0 .. topology.get_vertices().len()).find_map(|start| {
+            let mut path = vec![start];
+            find_path(topology, &mut path, length, used).then_some(path)
+        }).unwrap();
+
+        for pair in path.windows(2) {
+            let (edge, _) = topology.get_vertex_edges(pair[0]).iter()
+                .zip(topology.get_vertex_neighbors(pair[0]))
+                .find(|(_, n)| **n == pair[1])
+                .unwrap();
+            game.place_road(&topology.get_edges()[*edge], player);
+        }
+        used.extend(path.iter());
+        path
+    }
+
+    /**
+     * Back tracking to extend a path without touching itself or the used vertices
+     */
+    fn find_path(topology: &Topology, path: &mut Vec<VertexId>, length: usize, used: &[VertexId]) -> bool {
+        let v = *path.last().unwrap();
+        let is_free = |n: VertexId| {
+            !used.contains(&n) && topology.get_vertex_neighbors(n).iter().all(|m| !used.contains(m))
+        };
+        if path.len() == 1 && !is_free(v) {
+            return false
+        }
+        if path.len() > length {
+            return true
+        }
+
+        for n in topology.get_vertex_neighbors(v) {
+            // The next vertex may only touch the path where it joins it
+            let touches_path = topology.get_vertex_neighbors(*n).iter().any(|m| *m != v && path.contains(m));
+            if is_free(*n) && !path.contains(n) && !touches_path {
+                path.push(*n);
+                if find_path(topology, path, length, used) {
+                    return true
+                }
+                path.pop();
+            }
+        }
+        false
+    }
+
+    #[test]
+    fn longest_road_needs_five_segments() {
+        let mut game = Game::random_from_seed(4, "longest road".to_string());
+        let mut used = Vec::new();
+
+        build_road(&mut game, 1, 4, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.get_road_length(1), 4);
+        assert_eq!(game.longest_road_player, 0);
+
+        build_road(&mut game, 2, 5, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 2);
+    }
+
+    #[test]
+    fn longest_road_stays_with_holder_on_tie() {
+        let mut game = Game::random_from_seed(4, "longest road".to_string());
+        let mut used = Vec::new();
+
+        build_road(&mut game, 1, 5, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 1);
+
+        build_road(&mut game, 2, 5, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 1);
+
+        build_road(&mut game, 2, 6, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 2);
+    }
+
+    #[test]
+    fn broken_longest_road_passes_to_next_longest() {
+        let mut game = Game::random_from_seed(4, "longest road".to_string());
+        let mut used = Vec::new();
+
+        let path = build_road(&mut game, 1, 7, &mut used);
+        build_road(&mut game, 2, 5, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 1);
+
+        // A settlement in the middle of the road splits it into 3 and 4 segments
+        let vertex = game.board.get_topology().get_vertices()[path[3]];
+        game.place_building(&vertex, Building::Settlement, 3);
+        game.update_longest_road();
+        assert_eq!(game.get_road_length(1), 4);
+        assert_eq!(game.longest_road_player, 2);
+    }
+
+    #[test]
+    fn broken_longest_road_returns_to_bank_on_tie() {
+        let mut game = Game::random_from_seed(4, "longest road".to_string());
+        let mut used = Vec::new();
+
+        let path = build_road(&mut game, 1, 7, &mut used);
+        build_road(&mut game, 2, 5, &mut used);
+        build_road(&mut game, 3, 5, &mut used);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 1);
+
+        let vertex = game.board.get_topology().get_vertices()[path[3]];
+        game.place_building(&vertex, Building::Settlement, 4);
+        game.update_longest_road();
+        assert_eq!(game.longest_road_player, 0);
+    }
+
+}
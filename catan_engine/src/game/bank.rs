@@ -0,0 +1,197 @@
+use crate::board::tile::Resource;
+
+use super::{devs::{DevCard, DevHand}, hand::Hand, undo::Change, Game};
+
+// Resource cards of each type in the bank of the 5-6 player extension
+pub const EXTENSION_MAX_CARDS: u32 = 24;
+
+/**
+ * The bank holds every resource card that is not in a player's hand, and the
+ * development card deck. Resource cards only move between the bank and the
+ * players, or from one player to another, so the number of cards in the game
+ * never changes.
+ */
+pub struct Bank {
+    resources: Hand,
+    dev_deck: Vec<DevCard>,
+}
+
+impl Bank {
+
+    /**
+     * Create a bank holding a number of cards of every resource, and a shuffled
+     * development card deck. Cards are drawn from the end of the deck.
+     */
+    pub fn new(resource_count: u32, dev_deck: Vec<DevCard>) -> Self {
+        let resources = Resource::ALL.iter().fold(Hand::EMPTY, |hand, res| hand.with(*res, resource_count));
+        Bank { resources, dev_deck }
+    }
+
+    /**
+     * The resource cards left in the bank
+     */
+    pub fn get_resources(&self) -> Hand {
+        self.resources
+    }
+
+    /**
+     * The number of cards of a resource left in the bank
+     */
+    pub fn count(&self, res: Resource) -> u32 {
+        self.resources[res]
+    }
+
+    /**
+     * Check if the bank can pay out the cards
+     */
+    pub fn has(&self, cards: Hand) -> bool {
+        self.resources.has(cards)
+    }
+
+    /**
+     * The number of development cards left in the deck
+     */
+    pub fn get_dev_count(&self) -> usize {
+        self.dev_deck.len()
+    }
+
+    /**
+     * The development card on top of the deck
+     */
+    pub fn peek_dev(&self) -> Option<DevCard> {
+        self.dev_deck.last().copied()
+    }
+
+    /**
+     * Take the card nearest the top of the deck out of the deck, and return where it was
+     */
+    fn draw_dev(&mut self, card: DevCard) -> usize {
+        match self.dev_deck.iter().rposition(|e| *e == card) {
+            Some(i) => {
+                self.dev_deck.remove(i);
+                i
+            }
+            None => panic!("No {:?} left in the development card deck", card),
+        }
+    }
+
+    /**
+     * Put a drawn card back where it was in the deck
+     */
+    pub(super) fn return_dev(&mut self, i: usize, card: DevCard) {
+        self.dev_deck.insert(i, card);
+    }
+
+    /**
+     * Replace the resource cards in the bank
+     */
+    pub(super) fn set_resources(&mut self, resources: Hand) {
+        self.resources = resources;
+    }
+
+}
+
+impl Game {
+
+    /**
+     * The bank of the game
+     */
+    pub fn get_bank(&self) -> &Bank {
+        &self.bank
+    }
+
+    /**
+     * Move resource cards from the bank to a player's hand
+     */
+    pub(super) fn take_from_bank(&mut self, player: u8, cards: Hand) {
+        if !self.bank.has(cards) {
+            panic!("The bank can not pay {} to player {}", cards, player);
+        }
+        self.log(Change::Bank(self.bank.resources));
+        self.bank.resources -= cards;
+        self.set_hand(player, self.get_hand(player) + cards);
+    }
+
+    /**
+     * Move resource cards from a player's hand to the bank
+     */
+    pub(super) fn give_to_bank(&mut self, player: u8, cards: Hand) {
+        let hand = self.get_hand(player);
+        if !hand.has(cards) {
+            panic!("Player {} can not pay {} to the bank", player, cards);
+        }
+        self.set_hand(player, hand - cards);
+        self.log(Change::Bank(self.bank.resources));
+        self.bank.resources += cards;
+    }
+
+    /**
+     * Move a development card from the deck to a player
+     */
+    pub(super) fn draw_dev(&mut self, player: u8, card: DevCard) {
+        let i = self.bank.draw_dev(card);
+        self.log(Change::DevDrawn(i, card));
+        self.set_devs(player, self.get_devs(player) + DevHand::of(card, 1));
+    }
+
+    /**
+     * Pay out the resources owed to the players for a resource. If the bank can not
+     * pay everyone nobody is paid, unless a single player is owed, who then takes
+     * what is left in the bank.
+     */
+    pub(super) fn pay_resource(&mut self, res: Resource, owed: &[u32]) {
+        let total: u32 = owed.iter().sum();
+        let players: Vec<u8> = (1 ..= owed.len() as u8).filter(|p| owed[(*p - 1) as usize] > 0).collect();
+        if total <= self.bank.count(res) {
+            players.into_iter().for_each(|p| {
+                self.take_from_bank(p, Hand::of(res, owed[(p - 1) as usize]));
+            });
+        } else if players.len() == 1 {
+            self.take_from_bank(players[0], Hand::of(res, self.bank.count(res)));
+        }
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::tile::Resource;
+    use crate::game::{hand::Hand, Game};
+
+    /**
+     * A game with only a few cards of ore left in the bank
+     */
+    fn game_with_ore(count: u32) -> Game {
+        let mut game = Game::random_from_seed(4, "bank".to_string());
+        let resources = game.bank.get_resources().with(Resource::Ore, count);
+        game.bank.set_resources(resources);
+        game
+    }
+
+    #[test]
+    fn pay_resource_pays_everyone_when_the_bank_has_enough() {
+        let mut game = game_with_ore(3);
+        game.pay_resource(Resource::Ore, &[1, 0, 2, 0]);
+        assert_eq!(game.get_hand(1), Hand::of(Resource::Ore, 1));
+        assert_eq!(game.get_hand(3), Hand::of(Resource::Ore, 2));
+        assert_eq!(game.bank.count(Resource::Ore), 0);
+    }
+
+    #[test]
+    fn pay_resource_pays_nobody_when_the_bank_runs_short() {
+        let mut game = game_with_ore(2);
+        game.pay_resource(Resource::Ore, &[1, 0, 2, 0]);
+        assert!(game.get_hand(1).is_empty());
+        assert!(game.get_hand(3).is_empty());
+        assert_eq!(game.bank.count(Resource::Ore), 2);
+    }
+
+    #[test]
+    fn pay_resource_gives_a_single_player_what_is_left() {
+        let mut game = game_with_ore(1);
+        game.pay_resource(Resource::Ore, &[0, 2, 0, 0]);
+        assert_eq!(game.get_hand(2), Hand::of(Resource::Ore, 1));
+        assert_eq!(game.bank.count(Resource::Ore), 0);
+    }
+
+}
@@ -1,55 +1,358 @@
+use std::{fmt::{Debug, Display}, ops::{Add, AddAssign, Index, Sub, SubAssign}};
+
+use rand::{seq::SliceRandom, Rng};
+
+/**
+ * Number of each development card in the deck
+ */
+const KNIGHT_FREQUENCY: u8 = 14;
+const VP_FREQUENCY: u8 = 5;
+const ROAD_FREQUENCY: u8 = 2;
+const YOP_FREQUENCY: u8 = 2;
+const MONO_FREQUENCY: u8 = 2;
+
+/**
+ * Number of each development card in the deck of the 5-6 player extension
+ */
+const EXTENSION_KNIGHT_FREQUENCY: u8 = 20;
+const EXTENSION_VP_FREQUENCY: u8 = 5;
+const EXTENSION_ROAD_FREQUENCY: u8 = 3;
+const EXTENSION_YOP_FREQUENCY: u8 = 3;
+const EXTENSION_MONO_FREQUENCY: u8 = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum DevCard {
+    Knight, VictoryPoint, RoadBuilding, YearOfPlenty, Monopoly
+}
+
+impl DevCard {
+
+    // Every development card type
+    pub const ALL: [DevCard; 5] = [
+        DevCard::Knight, DevCard::VictoryPoint, DevCard::RoadBuilding, DevCard::YearOfPlenty, DevCard::Monopoly
+    ];
+
+    pub fn as_notation(&self) -> String {
+        match self {
+            DevCard::Knight => "K".to_string(),
+            DevCard::VictoryPoint => "V".to_string(),
+            DevCard::RoadBuilding => "R".to_string(),
+            DevCard::YearOfPlenty => "Y".to_string(),
+            DevCard::Monopoly => "M".to_string(),
+        }
+    }
+
+    pub fn from_string(c: &str) -> DevCard {
+        match c {
+            "K" => DevCard::Knight,
+            "V" => DevCard::VictoryPoint,
+            "R" => DevCard::RoadBuilding,
+            "Y" => DevCard::YearOfPlenty,
+            "M" => DevCard::Monopoly,
+            _ => unimplemented!("Invalid development card type"),
+        }
+    }
+
+}
+
+/**
+ * Create the shuffled development card deck. Cards are drawn from the end of the deck.
+ */
+pub fn new_deck<R: Rng>(rng: &mut R) -> Vec<DevCard> {
+    shuffled_deck(rng, [
+        (DevCard::Knight, KNIGHT_FREQUENCY),
+        (DevCard::VictoryPoint, VP_FREQUENCY),
+        (DevCard::RoadBuilding, ROAD_FREQUENCY),
+        (DevCard::YearOfPlenty, YOP_FREQUENCY),
+        (DevCard::Monopoly, MONO_FREQUENCY),
+    ])
+}
+
+/**
+ * Create the shuffled development card deck of the 5-6 player extension
+ */
+pub fn new_extension_deck<R: Rng>(rng: &mut R) -> Vec<DevCard> {
+    shuffled_deck(rng, [
+        (DevCard::Knight, EXTENSION_KNIGHT_FREQUENCY),
+        (DevCard::VictoryPoint, EXTENSION_VP_FREQUENCY),
+        (DevCard::RoadBuilding, EXTENSION_ROAD_FREQUENCY),
+        (DevCard::YearOfPlenty, EXTENSION_YOP_FREQUENCY),
+        (DevCard::Monopoly, EXTENSION_MONO_FREQUENCY),
+    ])
+}
+
+fn shuffled_deck<R: Rng>(rng: &mut R, frequencies: [(DevCard, u8); 5]) -> Vec<DevCard> {
+    let mut deck = Vec::new();
+    for (card, frequency) in frequencies {
+        for _ in 0 .. frequency {
+            deck.push(card);
+        }
+    }
+    deck.shuffle(rng);
+    deck
+}
+
+/**
+ * Development card bits in a 16 bit integer, wide enough to hold every card of a type
+ * in the extension deck
+ * [2 bits 0 padding][2 bits mono][2 bits yop][2 bits road][5 bits knights][3 bit VPs]
+ */
+const fn layout(card: DevCard) -> (u8, u16) {
+    match card {
+        DevCard::Monopoly => (12, 0b11),
+        DevCard::YearOfPlenty => (10, 0b11),
+        DevCard::RoadBuilding => (8, 0b11),
+        DevCard::Knight => (3, 0b11111),
+        DevCard::VictoryPoint => (0, 0b111),
+    }
+}
+
+const PADDING_SHIFT: u8 = 14;
+
+// Every count a card type can have, so a count can be returned by reference
+static COUNTS: [u16; 32] = [
+    0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+];
+
 /**
- * Development card bits in a 16 bit integer
- * [3 bits 0 padding][2 bits mono][2 bits yop][2 bits road][4 bits knights][3 bit VPs]
+ * Development cards held by a player
  */
-const MONO_MASK: u16    = 0b0001100000000000;
-const YOP_MASK: u16     = 0b0000011000000000;
-const ROAD_MASK: u16    = 0b0000000110000000;
-const KNIGHT_MASK: u16  = 0b0000000001111000;
-const VP_MASK:u16       = 0b0000000000000111;
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct DevHand(u16);
 
-const MONO_SHIFT: u8 = 11;
-const YOP_SHIFT: u8 = 9;
-const ROAD_SHIFT: u8 = 7;
-const KNIGHT_SHIFT: u8 = 3;
-const VP_SHIFT: u8 = 0;
+/**
+ * A change to a development card hand that would leave a card type with less than no
+ * cards, or with more cards than the hand can hold
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DevHandError {
+    Underflow(DevCard),
+    Overflow(DevCard),
+}
+
+impl DevHand {
+
+    pub const EMPTY: DevHand = DevHand(0);
+
+    /**
+     * A hand holding a number of development cards of a single type
+     */
+    pub fn of(card: DevCard, count: u16) -> DevHand {
+        DevHand::EMPTY.with(card, count)
+    }
+
+    /**
+     * Read a hand from its encoded form
+     */
+    pub fn from_bits(bits: u16) -> DevHand {
+        if bits >> PADDING_SHIFT != 0 {
+            panic!("Invalid encoded development cards: {:016b}", bits);
+        }
+        DevHand(bits)
+    }
+
+    /**
+     * The encoded form of the hand
+     */
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /**
+     * The number of development cards of a type
+     */
+    pub fn count(&self, card: DevCard) -> u16 {
+        let (shift, mask) = layout(card);
+        (self.0 >> shift) & mask
+    }
+
+    /**
+     * The number of development cards in the hand
+     */
+    pub fn size(&self) -> u16 {
+        DevCard::ALL.iter().map(|card| self.count(*card)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /**
+     * Check if the hand contains the cards in another hand
+     */
+    pub fn has(&self, cards: DevHand) -> bool {
+        DevCard::ALL.iter().all(|card| self.count(*card) >= cards.count(*card))
+    }
+
+    /**
+     * The same hand with a different number of development cards of a type
+     */
+    pub fn with(self, card: DevCard, count: u16) -> DevHand {
+        let (shift, mask) = layout(card);
+        if count > mask {
+            panic!("A hand can not hold {} {:?} cards", count, card);
+        }
+        DevHand((self.0 & !(mask << shift)) | (count << shift))
+    }
+
+    /**
+     * Add the cards of another hand, or fail if a card type has too many cards
+     */
+    pub fn checked_add(self, cards: DevHand) -> Result<DevHand, DevHandError> {
+        DevCard::ALL.iter().try_fold(self, |hand, card| {
+            match hand.count(*card) + cards.count(*card) {
+                count if count > layout(*card).1 => Err(DevHandError::Overflow(*card)),
+                count => Ok(hand.with(*card, count)),
+            }
+        })
+    }
+
+    /**
+     * Take out the cards of another hand, or fail if the hand does not have them
+     */
+    pub fn checked_sub(self, cards: DevHand) -> Result<DevHand, DevHandError> {
+        DevCard::ALL.iter().try_fold(self, |hand, card| {
+            match hand.count(*card).checked_sub(cards.count(*card)) {
+                Some(count) => Ok(hand.with(*card, count)),
+                None => Err(DevHandError::Underflow(*card)),
+            }
+        })
+    }
+
+    /**
+     * The number of cards of every type
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (DevCard, u16)> + '_ {
+        DevCard::ALL.iter().map(|card| (*card, self.count(*card)))
+    }
+
+    /**
+     * Every card in the hand
+     */
+    pub fn cards(&self) -> impl Iterator<Item = DevCard> + '_ {
+        self.iter().flat_map(|(card, count)| std::iter::repeat_n(card, count as usize))
+    }
 
-pub fn get_mono_count(hand: &u16) -> u16 {
-    (hand % MONO_MASK) >> MONO_SHIFT
 }
 
-pub fn get_yop_count(hand: &u16) -> u16 {
-    (hand % YOP_MASK) >> YOP_SHIFT
+impl Add for DevHand {
+    type Output = DevHand;
+
+    fn add(self, cards: DevHand) -> DevHand {
+        self.checked_add(cards).unwrap_or_else(|err| panic!("Can not add {} to {}: {}", cards, self, err))
+    }
 }
 
-pub fn get_road_count(hand: &u16) -> u16 {
-    (hand % ROAD_MASK) >> ROAD_SHIFT
+impl AddAssign for DevHand {
+    fn add_assign(&mut self, cards: DevHand) {
+        *self = *self + cards;
+    }
 }
 
-pub fn get_knight_count(hand: &u16) -> u16 {
-    (hand % KNIGHT_MASK) >> KNIGHT_SHIFT
+impl Sub for DevHand {
+    type Output = DevHand;
+
+    fn sub(self, cards: DevHand) -> DevHand {
+        self.checked_sub(cards).unwrap_or_else(|err| panic!("Can not take {} from {}: {}", cards, self, err))
+    }
 }
 
-pub fn get_vp_count(hand: &u16) -> u16 {
-    (hand % VP_MASK) >> VP_SHIFT
+impl SubAssign for DevHand {
+    fn sub_assign(&mut self, cards: DevHand) {
+        *self = *self - cards;
+    }
 }
 
-pub fn add_mono(hand: &u16, amount: i8) -> u16 {
-    (hand & !MONO_MASK) | (((get_mono_count(hand) as i8 - amount) as u16) << MONO_SHIFT)
+impl Index<DevCard> for DevHand {
+    type Output = u16;
+
+    fn index(&self, card: DevCard) -> &u16 {
+        &COUNTS[self.count(card) as usize]
+    }
 }
 
-pub fn add_yop(hand: &u16, amount: i8) -> u16 {
-    (hand & !YOP_MASK) | (((get_yop_count(hand) as i8 - amount) as u16) << YOP_SHIFT)
+/**
+ * Development cards are written as one letter per card, e.g. `KKV`
+ */
+impl Display for DevHand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.cards().try_for_each(|card| write!(f, "{}", card.as_notation()))
+    }
 }
 
-pub fn add_road(hand: &u16, amount: i8) -> u16 {
-    (hand & !ROAD_MASK) | (((get_road_count(hand) as i8 - amount) as u16) << ROAD_SHIFT)
+impl Debug for DevHand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DevHand({})", self)
+    }
 }
 
-pub fn add_knight(hand: &u16, amount: i8) -> u16 {
-    (hand & !KNIGHT_MASK) | (((get_knight_count(hand) as i8 - amount) as u16) << KNIGHT_SHIFT)
+impl Display for DevHandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DevHandError::Underflow(card) => write!(f, "not enough {:?} cards", card),
+            DevHandError::Overflow(card) => write!(f, "too many {:?} cards", card),
+        }
+    }
 }
 
-pub fn add_vp(hand: &u16, amount: i8) -> u16 {
-    (hand & !VP_MASK) | (((get_vp_count(hand) as i8 - amount) as u16) << VP_SHIFT)
-}
\ No newline at end of file
+impl std::error::Error for DevHandError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{layout, DevCard, DevHand, DevHandError};
+
+    #[test]
+    fn counts_do_not_overlap() {
+        for card in DevCard::ALL {
+            let max = layout(card).1;
+            let hand = DevHand::of(card, max);
+            for other in DevCard::ALL {
+                assert_eq!(hand[other], if other == card { max } else { 0 });
+            }
+        }
+    }
+
+    #[test]
+    fn add_adds_the_cards() {
+        let mut hand = DevHand::of(DevCard::Knight, 1);
+        hand += DevHand::of(DevCard::Knight, 2) + DevHand::of(DevCard::Monopoly, 1);
+        assert_eq!(hand[DevCard::Knight], 3);
+        assert_eq!(hand[DevCard::Monopoly], 1);
+        assert_eq!(hand.size(), 4);
+
+        hand -= DevHand::of(DevCard::Knight, 1);
+        assert_eq!(hand, DevHand::of(DevCard::Knight, 2) + DevHand::of(DevCard::Monopoly, 1));
+    }
+
+    #[test]
+    fn checked_sub_fails_on_underflow() {
+        let hand = DevHand::of(DevCard::RoadBuilding, 1);
+        assert_eq!(DevHand::EMPTY.checked_sub(hand), Err(DevHandError::Underflow(DevCard::RoadBuilding)));
+        assert_eq!(hand.checked_sub(DevHand::of(DevCard::YearOfPlenty, 1)), Err(DevHandError::Underflow(DevCard::YearOfPlenty)));
+        assert_eq!(hand.checked_sub(hand), Ok(DevHand::EMPTY));
+    }
+
+    #[test]
+    fn checked_add_fails_on_overflow() {
+        // Every card of a type in the extension deck fits in the hand
+        let knights = DevHand::of(DevCard::Knight, 20);
+        let roads = DevHand::of(DevCard::RoadBuilding, 3);
+        assert_eq!((knights + roads).count(DevCard::Knight), 20);
+
+        let full = DevHand::of(DevCard::Knight, layout(DevCard::Knight).1);
+        assert_eq!(full.checked_add(DevHand::of(DevCard::Knight, 1)), Err(DevHandError::Overflow(DevCard::Knight)));
+        assert_eq!(roads.checked_add(DevHand::of(DevCard::RoadBuilding, 1)), Err(DevHandError::Overflow(DevCard::RoadBuilding)));
+    }
+
+    #[test]
+    fn iter_and_display_follow_the_card_order() {
+        let hand = DevHand::of(DevCard::Monopoly, 1) + DevHand::of(DevCard::Knight, 2) + DevHand::of(DevCard::VictoryPoint, 1);
+        assert_eq!(hand.iter().collect::<Vec<_>>(), vec![
+            (DevCard::Knight, 2), (DevCard::VictoryPoint, 1), (DevCard::RoadBuilding, 0), (DevCard::YearOfPlenty, 0), (DevCard::Monopoly, 1),
+        ]);
+        assert_eq!(hand.to_string(), "KKVM");
+        assert_eq!(format!("{:?}", hand), "DevHand(KKVM)");
+        assert_eq!(DevHand::from_bits(hand.bits()), hand);
+    }
+
+}
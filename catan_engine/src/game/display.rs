@@ -1,10 +1,10 @@
-use std::fmt::format;
+use std::fmt::Display;
 
-use colored::{Color, ColoredString, Colorize};
+use colored::{Color, Colorize};
 
-use crate::{board::display::player_color, game::{devs, hand}};
+use crate::board::{display::player_color, tile::Resource};
 
-use super::Game;
+use super::{devs::{DevCard, DevHand}, hand::Hand, Game, GameResult};
 
 impl Game {
 
@@ -15,8 +15,8 @@ impl Game {
             let color = player_color(p);
             let hand = self.get_hand(p);
             let devs = self.get_devs(p);
-            let horiz_line = format!("+---------------------------------------+").color(color);
-            let vert_line = format!("|").color(color);
+            let horiz_line = "+---------------------------------------+".color(color);
+            let vert_line = "|".color(color);
             println!("{}", horiz_line);
             print!("{} ", vert_line);
             print!("{}                         {:<2}VP", format!("Player {}", p).color(color), points[(p - 1) as usize]);
@@ -27,19 +27,35 @@ impl Game {
             println!(" {}", vert_line);
             println!("{}", horiz_line);
             print!("{} ", vert_line);
-            print!("{}", Self::player_devs(p, devs));
+            print!("{}", Self::player_devs(devs));
+            println!(" {}", vert_line);
+            println!("{}", horiz_line);
+            print!("{} ", vert_line);
+            print!("{}", self.player_awards(p));
+            println!(" {}", vert_line);
+            println!("{}", horiz_line);
+            print!("{} ", vert_line);
+            print!("{}", self.player_pieces(p));
             println!(" {}", vert_line);
             println!("{}", horiz_line);
         }
+
+        // Print the bank
+        let horiz_line = "+---------------------------------------+";
+        println!("{}", horiz_line);
+        println!("| Bank                          {:>2} Devs |", self.bank.get_dev_count());
+        println!("{}", horiz_line);
+        println!("| {} |", Self::player_hand(self.bank.get_resources()));
+        println!("{}", horiz_line);
         self.board.print();
     }
 
-    fn player_hand(hand: u32) -> String {
-        let b = hand::get_brick_count(hand);
-        let l = hand::get_lumber_count(hand);
-        let o = hand::get_ore_count(hand);
-        let s = hand::get_sheep_count(hand);
-        let w = hand::get_wheat_count(hand);
+    fn player_hand(hand: Hand) -> String {
+        let b = hand[Resource::Brick];
+        let l = hand[Resource::Lumber];
+        let o = hand[Resource::Ore];
+        let s = hand[Resource::Sheep];
+        let w = hand[Resource::Wheat];
         format!("{}: {:>2} | {}: {:>2} | {}: {:>2} | {}: {:>2} | {}: {:>2}",
             "B".color(Color::Red),
             b.to_string().color(Color::White),
@@ -55,12 +71,12 @@ impl Game {
     }
 
     
-    fn player_devs(player: u8, hand: u16) -> String {
-        let k = devs::get_knight_count(&hand);
-        let r = devs::get_road_count(&hand);
-        let y = devs::get_yop_count(&hand);
-        let m = devs::get_mono_count(&hand);
-        let v = devs::get_vp_count(&hand);
+    fn player_devs(hand: DevHand) -> String {
+        let k = hand[DevCard::Knight];
+        let r = hand[DevCard::RoadBuilding];
+        let y = hand[DevCard::YearOfPlenty];
+        let m = hand[DevCard::Monopoly];
+        let v = hand[DevCard::VictoryPoint];
         format!("{}: {:>2} | {}: {:>2} | {}: {:>2} | {}: {:>2} | {}: {:>2}",
             "K".color(Color::Magenta),
             k.to_string().color(Color::White),
@@ -75,4 +91,48 @@ impl Game {
         )
     }
 
-}
\ No newline at end of file
+    /**
+     * Army size and road length, with a star marking the award holder
+     */
+    fn player_awards(&self, player: u8) -> String {
+        let army = format!("Army: {:>2}{}",
+            self.get_army_size(player),
+            if self.largest_army_player == player { "*" } else { " " },
+        );
+        let road = format!("Road: {:>2}{}",
+            self.get_road_length(player),
+            if self.longest_road_player == player { "*" } else { " " },
+        );
+        format!("{:<18}  {:>17}", army, road)
+    }
+
+    /**
+     * Pieces the player has left to build with
+     */
+    fn player_pieces(&self, player: u8) -> String {
+        let pieces = self.get_pieces(player);
+        format!("{:<37}", format!("Roads: {:>2} | Settles: {:>2} | Cities: {:>2}",
+            pieces.roads,
+            pieces.settlements,
+            pieces.cities,
+        ))
+    }
+
+}
+
+impl Display for GameResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.winner == 0 {
+            writeln!(f, "No winner after {} turns", self.turns)?;
+        } else {
+            writeln!(f, "Player {} wins after {} turns", self.winner, self.turns)?;
+        }
+        for (i, points) in self.points.iter().enumerate() {
+            write!(f, "{}", format!("Player {}: {} VP", i + 1, points).color(player_color((i + 1) as u8)))?;
+            if i + 1 < self.points.len() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,145 +1,304 @@
-use crate::board::tile::{BRICK_VALUE, LUMBER_VALUE, ORE_VALUE, SHEEP_VALUE, WHEAT_VALUE};
+use std::{fmt::{Debug, Display}, ops::{Add, AddAssign, Index, Sub, SubAssign}};
+
+use crate::board::tile::Resource;
 
 const CHUNK_SIZE: u8 = 6;
 const MASK: u32 = 0b111111;
-/**
- * Cards in player's hand
- */
-const BRICK_SHIFT: u8   = (BRICK_VALUE - 1) * CHUNK_SIZE;
-const LUMBER_SHIFT: u8  = (LUMBER_VALUE - 1) * CHUNK_SIZE;
-const ORE_SHIFT: u8     = (ORE_VALUE - 1) * CHUNK_SIZE;
-const SHEEP_SHIFT: u8   = (SHEEP_VALUE - 1) * CHUNK_SIZE;
-const WHEAT_SHIFT: u8   = (WHEAT_VALUE - 1) * CHUNK_SIZE;
-
-const BRICK_MASK: u32   = MASK << BRICK_SHIFT;
-const LUMBER_MASK: u32  = MASK << LUMBER_SHIFT;
-const ORE_MASK: u32     = MASK << ORE_SHIFT;
-const SHEEP_MASK: u32   = MASK << SHEEP_SHIFT;
-const WHEAT_MASK: u32   = MASK << WHEAT_SHIFT;
-
-pub const ROAD_RECIPE: u32 = (1 << BRICK_SHIFT) | (1 << LUMBER_SHIFT);
-pub const DEV_RECIPE: u32 = (1 << ORE_SHIFT) | (1 << WHEAT_SHIFT) | (1 << SHEEP_SHIFT);
-pub const SETTLE_RECIPE: u32 = (1 << BRICK_SHIFT) | (1 << LUMBER_SHIFT) | (1 << WHEAT_SHIFT) | (1 << SHEEP_SHIFT);
-pub const CITY_RECIPE: u32 = (3 << ORE_SHIFT) | (2 << WHEAT_SHIFT);
+
+// Most cards of a single resource a hand can hold
+pub const MAX_COUNT: u32 = MASK;
+
+// Every count a resource can have, so a count can be returned by reference
+static COUNTS: [u32; MASK as usize + 1] = {
+    let mut counts = [0; MASK as usize + 1];
+    let mut i = 0;
+    while i < counts.len() {
+        counts[i] = i as u32;
+        i += 1;
+    }
+    counts
+};
+
+pub const ROAD_RECIPE: Hand = Hand::of_values(&[(Resource::Brick, 1), (Resource::Lumber, 1)]);
+pub const DEV_RECIPE: Hand = Hand::of_values(&[(Resource::Ore, 1), (Resource::Wheat, 1), (Resource::Sheep, 1)]);
+pub const SETTLE_RECIPE: Hand = Hand::of_values(&[(Resource::Brick, 1), (Resource::Lumber, 1), (Resource::Wheat, 1), (Resource::Sheep, 1)]);
+pub const CITY_RECIPE: Hand = Hand::of_values(&[(Resource::Ore, 3), (Resource::Wheat, 2)]);
 
 // Maximum allowed number of cards in circulation
 pub const MAX_CARDS: u32 = 19;
 
-// MARK - helper utility functions with explicit names
-pub fn get_brick_count(hand: u32) -> u32 {
-    (hand & BRICK_MASK) >> BRICK_SHIFT
-}
+/**
+ * Resource cards in a player's hand, 6 bits for each resource in the order of their values
+ * [2 bits 0 padding][6 bits wheat][6 bits sheep][6 bits ore][6 bits lumber][6 bits brick]
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hand(u32);
 
-pub fn get_lumber_count(hand: u32) -> u32 {
-    (hand & LUMBER_MASK) >> LUMBER_SHIFT
+/**
+ * A change to a hand that would leave a resource with less than no cards, or with
+ * more cards than the hand can hold
+ */
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HandError {
+    Underflow(Resource),
+    Overflow(Resource),
 }
 
-pub fn get_ore_count(hand: u32) -> u32 {
-    (hand & ORE_MASK) >> ORE_SHIFT
+// The resources are declared in the order of their values
+const fn shift(res: Resource) -> u8 {
+    res as u8 * CHUNK_SIZE
 }
 
-pub fn get_sheep_count(hand: u32) -> u32 {
-    (hand & SHEEP_MASK) >> SHEEP_SHIFT
-}
+impl Hand {
 
-pub fn get_wheat_count(hand: u32) -> u32 {
-    (hand & WHEAT_MASK) >> WHEAT_SHIFT
-}
+    pub const EMPTY: Hand = Hand(0);
+
+    /**
+     * A hand holding a number of cards of a single resource
+     */
+    pub fn of(res: Resource, count: u32) -> Hand {
+        Hand::EMPTY.with(res, count)
+    }
+
+    const fn of_values(values: &[(Resource, u32)]) -> Hand {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < values.len() {
+            bits |= values[i].1 << shift(values[i].0);
+            i += 1;
+        }
+        Hand(bits)
+    }
+
+    /**
+     * Read a hand from its encoded form
+     */
+    pub fn from_bits(bits: u32) -> Hand {
+        if bits >> (CHUNK_SIZE * 5) != 0 {
+            panic!("Invalid encoded hand: {:032b}", bits);
+        }
+        Hand(bits)
+    }
+
+    /**
+     * The encoded form of the hand
+     */
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /**
+     * The number of cards of a resource
+     */
+    pub fn count(&self, res: Resource) -> u32 {
+        (self.0 >> shift(res)) & MASK
+    }
+
+    /**
+     * The number of cards in the hand
+     */
+    pub fn size(&self) -> u32 {
+        Resource::ALL.iter().map(|res| self.count(*res)).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /**
+     * Check if the hand contains the cards in another hand
+     */
+    pub fn has(&self, cards: Hand) -> bool {
+        Resource::ALL.iter().all(|res| self.count(*res) >= cards.count(*res))
+    }
+
+    /**
+     * The same hand with a different number of cards of a resource
+     */
+    pub fn with(self, res: Resource, count: u32) -> Hand {
+        if count > MAX_COUNT {
+            panic!("A hand can not hold {} {:?} cards", count, res);
+        }
+        Hand((self.0 & !(MASK << shift(res))) | (count << shift(res)))
+    }
+
+    /**
+     * Add the cards of another hand, or fail if a resource has too many cards
+     */
+    pub fn checked_add(self, cards: Hand) -> Result<Hand, HandError> {
+        Resource::ALL.iter().try_fold(self, |hand, res| {
+            match hand.count(*res) + cards.count(*res) {
+                count if count > MAX_COUNT => Err(HandError::Overflow(*res)),
+                count => Ok(hand.with(*res, count)),
+            }
+        })
+    }
+
+    /**
+     * Take out the cards of another hand, or fail if the hand does not have them
+     */
+    pub fn checked_sub(self, cards: Hand) -> Result<Hand, HandError> {
+        Resource::ALL.iter().try_fold(self, |hand, res| {
+            match hand.count(*res).checked_sub(cards.count(*res)) {
+                Some(count) => Ok(hand.with(*res, count)),
+                None => Err(HandError::Underflow(*res)),
+            }
+        })
+    }
+
+    /**
+     * The number of cards of every resource
+     */
+    pub fn iter(&self) -> impl Iterator<Item = (Resource, u32)> + '_ {
+        Resource::ALL.iter().map(|res| (*res, self.count(*res)))
+    }
+
+    /**
+     * Every card in the hand, in the order of the resource values
+     */
+    pub fn cards(&self) -> impl Iterator<Item = Resource> + '_ {
+        self.iter().flat_map(|(res, count)| std::iter::repeat_n(res, count as usize))
+    }
+
+    /**
+     * Get every hand of `size` cards that can be taken out of the hand
+     */
+    pub fn sub_hands(&self, size: u32) -> Vec<Hand> {
+        fn fill(hand: &Hand, i: usize, size: u32, sub_hand: Hand, vec: &mut Vec<Hand>) {
+            if size == 0 {
+                vec.push(sub_hand);
+                return
+            }
+            if i == Resource::ALL.len() {
+                return
+            }
+            let res = Resource::ALL[i];
+            for count in 0 ..= hand.count(res).min(size) {
+                fill(hand, i + 1, size - count, sub_hand.with(res, count), vec);
+            }
+        }
+
+        let mut vec = Vec::new();
+        fill(self, 0, size, Hand::EMPTY, &mut vec);
+        vec
+    }
 
-pub fn add_brick(hand: u32, amount: i8) -> u32 {
-    (hand & !BRICK_MASK) | (((get_brick_count(hand) as i8 + amount) as u32) << BRICK_SHIFT)
 }
 
-pub fn add_lumber(hand: u32, amount: i8) -> u32 {
-    (hand & !LUMBER_MASK) | (((get_lumber_count(hand) as i8 + amount) as u32) << LUMBER_SHIFT)
+impl Add for Hand {
+    type Output = Hand;
+
+    fn add(self, cards: Hand) -> Hand {
+        self.checked_add(cards).unwrap_or_else(|err| panic!("Can not add {} to {}: {}", cards, self, err))
+    }
 }
 
-pub fn add_ore(hand: u32, amount: i8) -> u32 {
-    (hand & !ORE_MASK) | (((get_ore_count(hand) as i8 + amount) as u32) << ORE_SHIFT)
+impl AddAssign for Hand {
+    fn add_assign(&mut self, cards: Hand) {
+        *self = *self + cards;
+    }
 }
 
-pub fn add_sheep(hand: u32, amount: i8) -> u32 {
-    (hand & !SHEEP_MASK) | (((get_sheep_count(hand) as i8 + amount) as u32) << SHEEP_SHIFT)
+impl Sub for Hand {
+    type Output = Hand;
+
+    fn sub(self, cards: Hand) -> Hand {
+        self.checked_sub(cards).unwrap_or_else(|err| panic!("Can not take {} from {}: {}", cards, self, err))
+    }
 }
 
-pub fn add_wheat(hand: u32, amount: i8) -> u32 {
-    (hand & !WHEAT_MASK) | (((get_wheat_count(hand) as i8 + amount) as u32) << WHEAT_SHIFT)
+impl SubAssign for Hand {
+    fn sub_assign(&mut self, cards: Hand) {
+        *self = *self - cards;
+    }
 }
 
-// MARK - controls over the hand without having to know the resource
+impl Index<Resource> for Hand {
+    type Output = u32;
 
-/**
- * Add a resource to a hand using the resource ID
- * 
- * This is implemented for efficiency
- */
-pub fn add(hand: u32, res: u8, amount: i8) -> u32 {
-    let shift: u8 = (res - 1) * CHUNK_SIZE;
-    let mask: u32 = MASK << shift;
-    let masked_hand = hand & !mask;
-    let value = (hand & mask) >> shift;
-    masked_hand | (((value as i8 + amount) as u32) << shift)
+    fn index(&self, res: Resource) -> &u32 {
+        &COUNTS[self.count(res) as usize]
+    }
 }
 
 /**
- * Get the resource count using the resource ID
- * 
- * This is implemented for efficiency
+ * A hand is written as one resource letter per card, e.g. `BBW`
  */
-pub fn count(hand: u32, res: u8) -> u32 {
-    let shift: u8 = (res - 1) * CHUNK_SIZE;
-    let mask = MASK << shift;
-    (hand & mask) >> shift
+impl Display for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.cards().try_for_each(|res| write!(f, "{}", res.as_notation()))
+    }
 }
 
-/**
- * Get the number of cards in a hand
- */
-pub fn size(hand: u32) -> u32 {
-    (hand & MASK)
-    + ((hand >> CHUNK_SIZE * 1) & MASK)
-    + ((hand >> CHUNK_SIZE * 2) & MASK)
-    + ((hand >> CHUNK_SIZE * 3) & MASK)
-    + ((hand >> CHUNK_SIZE * 4) & MASK)
+impl Debug for Hand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Hand({})", self)
+    }
 }
 
-/**
- * Check if a hand contains the cards in another hand
- */
-pub fn has(hand: u32, has: u32) -> bool {
-    for i in 1 ..= 5 {
-        if count(hand, i) < count(has, i) {
-            return false
+impl Display for HandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandError::Underflow(res) => write!(f, "not enough {:?} cards", res),
+            HandError::Overflow(res) => write!(f, "too many {:?} cards", res),
         }
     }
-    true
 }
 
-/**
- * Subtract values of one hand from another
- */
-pub fn subtract(hand: u32, value: u32) -> u32 {
-    let mut new = 0;
-    for i in 0 .. 5 {
-        let shift = CHUNK_SIZE * i;
-        new |= ((hand >> shift) & MASK) - ((value >> shift) & MASK) << shift
+impl std::error::Error for HandError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::tile::Resource;
+
+    use super::{Hand, HandError, MAX_COUNT};
+
+    #[test]
+    fn counts_do_not_overlap() {
+        for res in Resource::ALL {
+            let hand = Hand::of(res, MAX_COUNT);
+            for other in Resource::ALL {
+                assert_eq!(hand[other], if other == res { MAX_COUNT } else { 0 });
+            }
+        }
     }
-    new
-}
 
-/**
- * Utility function to help format the binary representation
- * oh a hand (or othER BINARY ENCODED VALUES)
- */
-pub fn format_binary_u32(i: u32, n: usize, p: usize) -> String {
-    let format = format!("{:032b}", i);
-    let padding: String = format.chars().take(p).collect();
-    let format: String = format.chars().skip(p).collect();
-    let chunked = format.chars()
-        .collect::<Vec<char>>()
-        .chunks(n)
-        .map(|c| c.iter().collect::<String>())
-        .collect::<Vec<String>>()
-        .join(" ");
-    format!("{} {}", padding, chunked)
-}
\ No newline at end of file
+    #[test]
+    fn checked_sub_fails_on_underflow() {
+        assert_eq!(Hand::EMPTY.checked_sub(Hand::of(Resource::Brick, 1)), Err(HandError::Underflow(Resource::Brick)));
+
+        let hand = Hand::of(Resource::Ore, 2) + Hand::of(Resource::Wheat, 1);
+        assert_eq!(hand.checked_sub(Hand::of(Resource::Wheat, 2)), Err(HandError::Underflow(Resource::Wheat)));
+        assert_eq!(hand.checked_sub(Hand::of(Resource::Ore, 1)), Ok(Hand::of(Resource::Ore, 1) + Hand::of(Resource::Wheat, 1)));
+        assert_eq!(hand - hand, Hand::EMPTY);
+    }
+
+    #[test]
+    fn checked_add_fails_on_overflow() {
+        let full = Hand::of(Resource::Sheep, MAX_COUNT);
+        assert_eq!(full.checked_add(Hand::of(Resource::Sheep, 1)), Err(HandError::Overflow(Resource::Sheep)));
+        assert_eq!(full.checked_add(Hand::of(Resource::Lumber, 1)), Ok(full.with(Resource::Lumber, 1)));
+
+        let hand = Hand::of(Resource::Sheep, MAX_COUNT - 1) + Hand::of(Resource::Sheep, 1);
+        assert_eq!(hand, full);
+    }
+
+    #[test]
+    #[should_panic]
+    fn sub_panics_on_underflow() {
+        let _ = Hand::of(Resource::Brick, 1) - Hand::of(Resource::Lumber, 1);
+    }
+
+    #[test]
+    fn iter_and_display_follow_the_resource_order() {
+        let hand = Hand::of(Resource::Wheat, 1) + Hand::of(Resource::Brick, 2) + Hand::of(Resource::Sheep, 1);
+        assert_eq!(hand.size(), 4);
+        assert_eq!(hand.iter().collect::<Vec<_>>(), vec![
+            (Resource::Brick, 2), (Resource::Lumber, 0), (Resource::Ore, 0), (Resource::Sheep, 1), (Resource::Wheat, 1),
+        ]);
+        assert_eq!(hand.to_string(), "BBSW");
+        assert_eq!(format!("{:?}", hand), "Hand(BBSW)");
+        assert_eq!(Hand::EMPTY.to_string(), "");
+    }
+
+}
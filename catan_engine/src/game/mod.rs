@@ -1,48 +1,101 @@
-mod devs;
-pub mod hand;
-mod action;
+pub(crate) mod devs;
+pub(crate) mod bank;
+pub(crate) mod agent;
+mod awards;
+pub(crate) mod hand;
+pub(crate) mod action;
 mod display;
+mod notation;
+pub(crate) mod phase;
+pub(crate) mod pieces;
+pub(crate) mod trade;
+mod undo;
+pub(crate) mod validate;
 
-use std::io::stdin;
+use action::{PlayerAction, RecordedAction};
+use agent::{PlayerAgent, RandomAgent};
+use bank::{Bank, EXTENSION_MAX_CARDS};
+use devs::{DevCard, DevHand};
+use hand::Hand;
+use phase::TurnPhase;
+use pieces::Pieces;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use rand_seeder::Seeder;
+use trade::{TradeOffer, TradeResponse};
+use undo::{Change, RedoStep, UndoStep};
 
-use action::{GameAction, PlayerAction};
-use rand::Rng;
-use rand::{rngs::ThreadRng, seq::SliceRandom, thread_rng};
+use crate::board::{tile::Resource, building::{BUILDING_SHIFT, PLAYER_VALUE_MASK}, generate::PortPlacement, Board, Layout, STANDARD_RADIUS};
 
-use crate::board::{building::{BUILDING_SHIFT, PLAYER_VALUE_MASK}, Board};
+// Most players at the table, with the 5-6 player extension
+pub const MAX_PLAYERS: usize = 6;
+
+// Games with this many players use the extension board and the Special Building Phase
+const EXTENSION_PLAYER_COUNT: u8 = 5;
+
+// Number of times an agent is asked for a player to rob before one is robbed at random
+const MAX_ROB_ATTEMPTS: u8 = 3;
+
+// Victory points needed to win, unless the game is given another target
+pub const DEFAULT_VP_TARGET: u8 = 10;
+
+/**
+ * The outcome of a game played to the end
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub struct GameResult {
+    pub winner: u8,
+    pub points: Vec<u8>, // The final points of every player
+    pub turns: u32,
+}
 
 pub struct Game {
     player_count: u8,
-    pub board: Board,
-    hands: [u32; 4],
-    devs: [u16; 4],
-    played_knights: [u8; 4],
+    pub(crate) board: Board,
+    hands: [Hand; MAX_PLAYERS],
+    devs: [DevHand; MAX_PLAYERS],
+    played_knights: [u8; MAX_PLAYERS],
+    pieces: [Pieces; MAX_PLAYERS],
     largest_army_player: u8,
     longest_road_player: u8,
     max_cards: u8,
-    orbit: u8,
+    vp_target: u8,
+    turn_count: u32,
+    bank: Bank,
+    free_roads: u8,
+    discards: [u32; MAX_PLAYERS],
+    turn_player: u8,
+    has_rolled: bool,
+    dev_played: bool,
+    bought_devs: DevHand,
+    phase: TurnPhase,
+    trade_actions: Vec<PlayerAction>,
+    record: Vec<RecordedAction>,
+    changes: Vec<Change>,
+    undo_steps: Vec<UndoStep>,
+    redo_steps: Vec<RedoStep>,
+    seed: String,
+    rng: Pcg64,
+    agents: Vec<Box<dyn PlayerAgent>>,
 }
 
 impl Game {
 
-    pub fn get_points(&self) -> [u8; 4] {
-        let mut points: [u8; 4] = [0; 4];
+    pub fn get_points(&self) -> [u8; MAX_PLAYERS] {
+        let mut points: [u8; MAX_PLAYERS] = [0; MAX_PLAYERS];
 
         // Board points
-        self.board.buildings.iter().for_each(|a| {
-            a.iter().for_each(|b| {
-                b.iter().for_each(|building| {
-                    let player = building & PLAYER_VALUE_MASK;
-                    if building & PLAYER_VALUE_MASK != 0 {
-                        points[(player - 1) as usize] += building >> BUILDING_SHIFT
-                    }
-                })
-            });
+        self.board.buildings.iter().for_each(|building| {
+            let player = building & PLAYER_VALUE_MASK;
+            if building & PLAYER_VALUE_MASK != 0 {
+                points[(player - 1) as usize] += building >> BUILDING_SHIFT
+            }
         });
 
         // VP devs
         self.devs.iter().enumerate().for_each(|(player, hand)| {
-            points[player] += devs::get_vp_count(hand) as u8
+            points[player] += hand[DevCard::VictoryPoint] as u8
         });
 
         // Largest army
@@ -62,63 +115,119 @@ impl Game {
      * Check if this game is in a winning state
      */
     pub fn has_winner(&self) -> bool {
-        self.get_points().into_iter().max().unwrap() == 10
+        self.get_winner() != 0
+    }
+
+    /**
+     * The player who won the game, or 0 if nobody has won. The game ends on the turn
+     * of the winner, as soon as they reach the victory point target.
+     */
+    pub fn get_winner(&self) -> u8 {
+        let player = self.turn_player;
+        if self.phase == TurnPhase::Finished && player != 0 && self.get_points()[(player - 1) as usize] >= self.vp_target {
+            player
+        } else {
+            0
+        }
     }
 
+    /**
+     * The victory points needed to win
+     */
+    pub fn get_vp_target(&self) -> u8 {
+        self.vp_target
+    }
+
+    /**
+     * Change the victory points needed to win, before the game starts
+     */
+    pub fn set_vp_target(&mut self, target: u8) {
+        if target == 0 {
+            panic!("Invalid victory point target: {}", target);
+        }
+        self.vp_target = target;
+    }
 
     /**
-     * The the encoded hand of a player
+     * The number of turns started, counting the turn in progress
      */
-    pub fn get_hand(&self, player: u8) -> u32 {
+    pub fn get_turn_count(&self) -> u32 {
+        self.turn_count
+    }
+
+
+    /**
+     * The resource cards of a player
+     */
+    pub fn get_hand(&self, player: u8) -> Hand {
         self.hands[(player - 1) as usize]
     }
 
     /**
      * Update a player's hand
      */
-    pub fn set_hand(&mut self, player: u8, hand: u32) {
+    pub(crate) fn set_hand(&mut self, player: u8, hand: Hand) {
+        self.log(Change::Hand(player, self.get_hand(player)));
         self.hands[(player - 1) as usize] = hand
     }
 
     /**
-     * The encoded development cards of a player
+     * The board the game is played on
      */
-    pub fn get_devs(&self, player: u8) -> u16 {
-        self.devs[(player - 1) as usize]
+    pub fn get_board(&self) -> &Board {
+        &self.board
     }
 
-    pub fn start(&mut self) {
-        let rng = &mut thread_rng();
-        let mut action = GameAction::GameStart;
+    /**
+     * Every action applied to this game, in order
+     */
+    pub fn get_record(&self) -> &[RecordedAction] {
+        &self.record
+    }
 
-        // Phase for inital placements
-        while action != GameAction::PlacementFinished {
-            action = self.perform_game_action(rng, action);
-        }
+    /**
+     * The development cards of a player
+     */
+    pub fn get_devs(&self, player: u8) -> DevHand {
+        self.devs[(player - 1) as usize]
+    }
 
-        let mut player = 1;
-        while action != GameAction::Finished {
-            if self.has_winner() {
-                break;
-            }
-            let roll = GameAction::Roll(player, Self::roll(rng));
-            action = self.perform_game_action(rng, roll);
-            player += 1;
-            if player > self.player_count {
-                player = 1;
+    /**
+     * Play the game to the end, asking the agents for every decision
+     */
+    pub fn start(&mut self) -> GameResult {
+        while self.phase != TurnPhase::Finished {
+            if self.phase == TurnPhase::Discard {
+                self.call_players_discard();
+            } else {
+                let player = self.get_current_player();
+                let action = self.call_player(player);
+                if let Err(err) = self.apply(action.clone()) {
+                    panic!("Player {} agent played an illegal action {:?}: {}", player, action, err);
+                }
             }
-            self.print();
         }
 
-        println!("Game over");
+        GameResult {
+            winner: self.get_winner(),
+            points: self.get_points()[.. self.player_count as usize].to_vec(),
+            turns: self.turn_count,
+        }
+    }
+
+    /**
+     * Check if every other player may build after each turn, in games of 5 or 6 players
+     */
+    pub fn has_special_building_phase(&self) -> bool {
+        self.player_count >= EXTENSION_PLAYER_COUNT
     }
 
     /**
      * Roll the dice
      */
-    fn roll(rng: &mut ThreadRng) -> u8 {
-        let d1 = rng.gen_range(1 ..= 6);
-        let d2 = rng.gen_range(1 ..= 6);
+    fn roll(&mut self) -> u8 {
+        let d1 = self.rng.gen_range(1 ..= 6);
+        let d2 = self.rng.gen_range(1 ..= 6);
         d1 + d2
     }
 
@@ -127,52 +236,79 @@ impl Game {
      * the hand at all, it just returns a valid resource
      * to steal
      */
-    fn steal(hand: u32, rng: &mut ThreadRng) -> u8 {
-        let size = hand::size(hand);
-        let mut i = rng.gen_range(0 .. size);
-        for res in 1 ..= 5 {
-            let count = hand::count(hand, res);
-            if count > i {
-                return res
-            }
-            i -= count;
+    fn steal(&mut self, hand: Hand) -> Resource {
+        let i = self.rng.gen_range(0 .. hand.size());
+        match hand.cards().nth(i as usize) {
+            Some(res) => res,
+            None => panic!("Failed to steal from hand - this is an implementation error"),
         }
-        panic!("Failed to steal from hand - this is an implementation error");
     }
 
     /**
-     * Provide a list of actions to a player agent and select a move to play
+     * Ask a player agent for the action to play in the current phase
      */
-    pub fn call_player(&mut self, rng: &mut ThreadRng, player: u8, actions: Vec<PlayerAction>) {
-        // If there are no actions to choose from, let this function return. Otherwise ask the player
-        // agent for a move
-        let mut actions = actions;
-        while !actions.is_empty() {
-            println!("Actions: {}", actions.len());
-            // TODO - add player agent trait. For now we just pick a random move
-            actions.shuffle(&mut thread_rng());
-            let pop = actions.pop().unwrap();
-            actions = self.perform_player_action(rng, player, pop);
-            stdin().read_line(&mut String::new());
+    pub fn call_player(&mut self, player: u8) -> PlayerAction {
+        match self.phase {
+            TurnPhase::RobberSteal(_) => {
+                // Ask again until the agent picks a player that can be robbed, an agent
+                // that fails too many times forfeits the choice to a random player
+                let options = self.get_robbable_players(player, &self.board.robber);
+                for _ in 0 .. MAX_ROB_ATTEMPTS {
+                    let steal = self.call_player_rob(player, options);
+                    if options.get(steal) == Some(&true) {
+                        return PlayerAction::Rob((steal + 1) as u8)
+                    }
+                }
+                let mut actions = self.legal_actions();
+                let i = self.rng.gen_range(0 .. actions.len());
+                actions.swap_remove(i)
+            }
+            TurnPhase::TradeAnswer(_, offer) => {
+                // An answer the player can not pay for is taken as a rejection
+                let from = self.turn_player;
+                let response = self.call_player_trade_response(player, from, &offer);
+                let action = PlayerAction::AnswerTrade(response);
+                match self.validate(player, &action) {
+                    Ok(()) => action,
+                    Err(_) => PlayerAction::AnswerTrade(TradeResponse::Reject),
+                }
+            }
+            _ => {
+                let mut actions = self.legal_actions();
+                let i = self.with_agent(player, |agent, game| {
+                    agent.choose_action(game, player, &actions)
+                });
+                if i >= actions.len() {
+                    panic!("Player {} agent selected action {} of {}", player, i, actions.len());
+                }
+                actions.swap_remove(i)
+            }
         }
     }
 
     /**
-     * Ask the agent to discard cards from a player's hand after a "7 out"
+     * Ask every player who must discard for their cards before any of them discards,
+     * so no player sees what the others chose
      */
-    pub fn call_player_discard(&mut self, player: u8) -> u32 {
-        // TODO - call the user agent, this will randomly discard cards.
-        let mut hand = self.get_hand(player);
-        let size = hand::size(hand);
-        let mut discard_count = size / 2;
-        while discard_count > 0 {
-            let res: u8 = thread_rng().gen_range(1 ..= 5);
-            if hand::count(hand, res) > 0 {
-                discard_count -= 1;
-                hand = hand::add(hand, res, -1);
+    fn call_players_discard(&mut self) {
+        let players: Vec<u8> = (1 ..= self.player_count).filter(|p| self.get_discard_count(*p) > 0).collect();
+        let discards: Vec<(u8, Hand)> = players.into_iter().map(|p| (p, self.get_player_discard(p))).collect();
+
+        for (player, cards) in discards {
+            if let Err(err) = self.apply_as(player, PlayerAction::Discard(cards)) {
+                panic!("Player {} discard was not valid: {}", player, err);
             }
         }
-        hand
+    }
+
+    /**
+     * Ask the agent to discard cards from a player's hand after a "7 out"
+     */
+    pub fn call_player_discard(&mut self, player: u8) -> Hand {
+        let count = self.get_discard_count(player);
+        self.with_agent(player, |agent, game| {
+            agent.choose_discard(game, player, count)
+        })
     }
 
     /**
@@ -183,28 +319,148 @@ impl Game {
      * 
      * Typically we index players starting from 1, in this case player 1 is 0.
      */
-    pub fn call_player_rob(&mut self, options: [bool; 4]) -> usize {
-        // TODO - call the user agent, this is just randomnly picking a player to rob
-        let mut players = options.into_iter()
-            .enumerate()
-            .filter(|(_, b) | { *b }).map(|(i, _)| { i })
-            .collect::<Vec<usize>>();
-        players.shuffle(&mut thread_rng());
-        players.pop().unwrap()
+    pub fn call_player_rob(&mut self, player: u8, options: [bool; MAX_PLAYERS]) -> usize {
+        self.with_agent(player, |agent, game| {
+            agent.choose_robbery(game, player, options)
+        })
+    }
+
+    /**
+     * Ask the agent of a player to answer a trade offer from the active player
+     */
+    pub fn call_player_trade_response(&mut self, player: u8, from: u8, offer: &TradeOffer) -> TradeResponse {
+        self.with_agent(player, |agent, game| {
+            agent.respond_to_trade(game, player, from, offer)
+        })
+    }
+
+    /**
+     * Lend a player's agent to a closure along with a read only view of the game.
+     * The agents are taken out of the game while the closure runs so the agent
+     * can be mutably borrowed at the same time.
+     */
+    fn with_agent<T>(&mut self, player: u8, f: impl FnOnce(&mut dyn PlayerAgent, &Game) -> T) -> T {
+        let mut agents = std::mem::take(&mut self.agents);
+        let result = f(agents[(player - 1) as usize].as_mut(), self);
+        self.agents = agents;
+        result
+    }
+
+    /**
+     * Replace the agent making the decisions for a player
+     */
+    pub fn set_agent(&mut self, player: u8, agent: Box<dyn PlayerAgent>) {
+        self.agents[(player - 1) as usize] = agent;
     }
 
+    /**
+     * Create a game with a random board without providing a seed
+     */
     pub fn random(player_count: u8) -> Game {
+        Self::random_from_seed(player_count, Self::random_seed())
+    }
+
+    /**
+     * Create a game with a random board. The seed controls the board, the development
+     * card deck, the dice, the steals and the default agents, so the same seed always
+     * plays out the same game. Games of 5 or 6 players are played on the extension board.
+     */
+    pub fn random_from_seed(player_count: u8, seed: String) -> Game {
+        let mut rng: Pcg64 = Seeder::from(&seed).make_rng();
+        let layout = if player_count >= EXTENSION_PLAYER_COUNT {
+            Layout::Extension
+        } else {
+            Layout::Hexagon(STANDARD_RADIUS)
+        };
+        let board = Board::random_from_rng(&mut rng, layout, PortPlacement::Random);
+        let mut game = Self::from_rng(player_count, board, seed, rng);
+        game.advance();
+        game
+    }
+
+    /**
+     * Create a game on a board, waiting on the first placement
+     */
+    pub fn new(player_count: u8, board: Board, seed: String) -> Game {
+        let mut game = Self::unstarted(player_count, board, seed);
+        game.advance();
+        game
+    }
+
+    /**
+     * Create a game on a board before the game has started, for replaying a record
+     */
+    pub(super) fn unstarted(player_count: u8, board: Board, seed: String) -> Game {
+        let rng: Pcg64 = Seeder::from(&seed).make_rng();
+        Self::from_rng(player_count, board, seed, rng)
+    }
+
+    /**
+     * Create a game drawing all of its randomness from the generator. The game has not
+     * started yet.
+     */
+    fn from_rng(player_count: u8, board: Board, seed: String, mut rng: Pcg64) -> Game {
+        if !(2 ..= MAX_PLAYERS as u8).contains(&player_count) {
+            panic!("Invalid player count: {}", player_count);
+        }
+
+        let (resource_count, deck) = if player_count >= EXTENSION_PLAYER_COUNT {
+            (EXTENSION_MAX_CARDS, devs::new_extension_deck(&mut rng))
+        } else {
+            (hand::MAX_CARDS, devs::new_deck(&mut rng))
+        };
+        let bank = Bank::new(resource_count, deck);
+        let agents = (0 .. player_count).map(|_| {
+            let agent_rng = Pcg64::from_rng(&mut rng).unwrap();
+            Box::new(RandomAgent::new(agent_rng)) as Box<dyn PlayerAgent>
+        }).collect();
         Game {
             player_count,
-            board: Board::random(),
-            hands: [0; 4],
-            devs: [0; 4],
-            played_knights: [0; 4],
+            board,
+            hands: [Hand::EMPTY; MAX_PLAYERS],
+            devs: [DevHand::EMPTY; MAX_PLAYERS],
+            played_knights: [0; MAX_PLAYERS],
+            pieces: [Pieces::new(); MAX_PLAYERS],
             largest_army_player: 0,
             longest_road_player: 0,
             max_cards: if player_count == 2 { 9 } else { 7 },
-            orbit: 0,
+            vp_target: DEFAULT_VP_TARGET,
+            turn_count: 0,
+            bank,
+            free_roads: 0,
+            discards: [0; MAX_PLAYERS],
+            turn_player: 0,
+            has_rolled: false,
+            dev_played: false,
+            bought_devs: DevHand::EMPTY,
+            phase: TurnPhase::GameStart,
+            trade_actions: Vec::new(),
+            record: Vec::new(),
+            changes: Vec::new(),
+            undo_steps: Vec::new(),
+            redo_steps: Vec::new(),
+            seed,
+            rng,
+            agents,
         }
     }
+
+    /**
+     * The seed this game was created with
+     */
+    pub fn get_seed(&self) -> &str {
+        &self.seed
+    }
+
+    /**
+     * Generate a seed for games created without one
+     */
+    fn random_seed() -> String {
+        thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(12)
+            .map(char::from)
+            .collect()
+    }
     
 }
\ No newline at end of file
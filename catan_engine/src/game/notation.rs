@@ -0,0 +1,423 @@
+use crate::board::{coordinate::{Coordinate2, EdgeCoord, VertexCoord}, tile::Resource, Board};
+
+use super::{action::{GameAction, PlayerAction, RecordedAction}, devs::DevCard, hand::Hand, trade::{TradeOffer, TradeResponse}, Game, MAX_PLAYERS};
+
+// Separator between the moves in the record
+const MOVE_SEPARATOR: char = ';';
+
+// Prefix of the game actions in the record, player actions are prefixed by the player
+const GAME_ACTION_PREFIX: char = '#';
+
+/**
+ * Player action codes. Longer codes must come before their prefixes so the
+ * longest code is matched.
+ */
+const PLAYER_CODES: [&str; 28] = [
+    "FP", "SP", "S1", "S2", "IR", "FR", "RM", "RB", "RO", "RJ", "ST", "BD", "PK", "PY", "PM", "PR", "YP", "MO",
+    "PT", "AC", "CO", "TR", "PA", "S", "C", "R", "D", "P",
+];
+
+// Separator between the cards given and received in a trade
+const TRADE_SEPARATOR: char = ':';
+
+impl Game {
+
+    /**
+     * Convert the game start to notation: `<num players>/<tiles>/<ports>`
+     */
+    pub fn as_notation(&self) -> String {
+        format!("{}/{}/{}", self.player_count, self.board.as_notation(), self.board.ports_as_notation())
+    }
+
+    /**
+     * Convert the game start and every recorded move to notation:
+     * `<num players>/<tiles>/<ports>/<moves>`
+     */
+    pub fn record_as_notation(&self) -> String {
+        let moves: Vec<String> = self.record.iter().map(|e| e.as_notation()).collect();
+        format!("{}/{}", self.as_notation(), moves.join(&MOVE_SEPARATOR.to_string()))
+    }
+
+    /**
+     * Create a game from notation: `<num players>/<tiles>/<ports>`, optionally followed
+     * by `/<moves>`. The moves are replayed onto the game start, then the game is played
+     * up to the next decision.
+     */
+    pub fn from_notation(str: String) -> Game {
+        Self::from_notation_with_seed(str, Self::random_seed())
+    }
+
+    /**
+     * Create a game from notation, with a seed for the development card deck,
+     * the dice and the default agents
+     */
+    pub fn from_notation_with_seed(str: String, seed: String) -> Game {
+        let str: String = str.chars().filter(|c| !Board::is_ignored_notation(*c)).collect();
+        let sections: Vec<&str> = str.split('/').collect();
+        if sections.len() != 3 && sections.len() != 4 {
+            panic!("Invalid game notation, expected <num players>/<tiles>/<ports>");
+        }
+
+        let player_count = match sections[0].parse::<u8>() {
+            Ok(count) if (2 ..= MAX_PLAYERS as u8).contains(&count) => count,
+            _ => panic!("Invalid player count in game notation: {}", sections[0]),
+        };
+
+        let mut board = Board::from_notation(sections[1].to_string());
+        board.set_ports_from_notation(sections[2]);
+
+        let mut game = Game::unstarted(player_count, board, seed);
+        if let Some(moves) = sections.get(3) {
+            moves.split(MOVE_SEPARATOR).filter(|e| !e.is_empty()).for_each(|e| {
+                game.apply_recorded_action(&RecordedAction::from_notation(e));
+            });
+        }
+        game.advance();
+        game
+    }
+
+}
+
+impl RecordedAction {
+
+    /**
+     * Convert a recorded action to move notation. Player actions are prefixed by the
+     * player, and game actions by `#`.
+     */
+    pub fn as_notation(&self) -> String {
+        match self {
+            RecordedAction::Game(action) => format!("{}{}", GAME_ACTION_PREFIX, game_action_as_notation(action)),
+            RecordedAction::Player(player, action) => format!("{}{}", player, player_action_as_notation(action)),
+        }
+    }
+
+    /**
+     * Create a recorded action from move notation
+     */
+    pub fn from_notation(str: &str) -> RecordedAction {
+        if let Some(action) = str.strip_prefix(GAME_ACTION_PREFIX) {
+            return RecordedAction::Game(game_action_from_notation(action));
+        }
+
+        let player = match str.chars().next().and_then(|c| c.to_digit(10)) {
+            Some(player) => player as u8,
+            None => panic!("Invalid move notation, missing player: {}", str),
+        };
+        RecordedAction::Player(player, player_action_from_notation(&str[1 ..]))
+    }
+
+}
+
+fn player_action_as_notation(action: &PlayerAction) -> String {
+    match action {
+        PlayerAction::FirstPlacement => "FP".to_string(),
+        PlayerAction::FirstSettlement(coord) => format!("S1{}", coord),
+        PlayerAction::InitialRoadPlacement(coord) => format!("IR{}", coord),
+        PlayerAction::SecondPlacement => "SP".to_string(),
+        PlayerAction::SecondSettlement(coord) => format!("S2{}", coord),
+        PlayerAction::Discard(cards) => format!("D{}", hand_as_notation(*cards)),
+        PlayerAction::PlaceSettlement(coord) => format!("S{}", coord),
+        PlayerAction::PlaceCity(coord) => format!("C{}", coord),
+        PlayerAction::PlaceRoad(coord) => format!("R{}", coord),
+        PlayerAction::RobberMove(coord) => format!("RM{}", coord.as_notation()),
+        PlayerAction::Rob(player) => format!("RB{}", player),
+        PlayerAction::RobberSteal(player, res) => format!("ST{}{}", player, resource_as_notation(*res)),
+        PlayerAction::Port(give, from, receive, to) => {
+            format!("P{}{}{}{}", give, resource_as_notation(*from), receive, resource_as_notation(*to))
+        }
+        PlayerAction::BuyDev => "BD".to_string(),
+        PlayerAction::PlayKnight => "PK".to_string(),
+        PlayerAction::PlayYOP => "PY".to_string(),
+        PlayerAction::PlayMono => "PM".to_string(),
+        PlayerAction::PlayRoad => "PR".to_string(),
+        PlayerAction::YearOfPlenty(a, b) => format!("YP{}{}", resource_as_notation(*a), resource_as_notation(*b)),
+        PlayerAction::Monopoly(res) => format!("MO{}", resource_as_notation(*res)),
+        PlayerAction::FreeRoad(coord) => format!("FR{}", coord),
+        PlayerAction::ProposeTrade(offer) => format!("PT{}", trade_as_notation(offer)),
+        PlayerAction::AnswerTrade(TradeResponse::Accept) => "AC".to_string(),
+        PlayerAction::AnswerTrade(TradeResponse::Reject) => "RJ".to_string(),
+        PlayerAction::AnswerTrade(TradeResponse::Counter(offer)) => format!("CO{}", trade_as_notation(offer)),
+        PlayerAction::Trade(other, give, receive) => {
+            format!("TR{}{}", other, trade_as_notation(&TradeOffer::new(*give, *receive)))
+        }
+        PlayerAction::Roll => "RO".to_string(),
+        PlayerAction::Pass => "PA".to_string(),
+    }
+}
+
+fn player_action_from_notation(str: &str) -> PlayerAction {
+    let code = match PLAYER_CODES.iter().find(|e| str.starts_with(*e)) {
+        Some(code) => *code,
+        None => panic!("Invalid move notation: {}", str),
+    };
+    let args = &str[code.len() ..];
+    let chars: Vec<char> = args.chars().collect();
+    match code {
+        "FP" => PlayerAction::FirstPlacement,
+        "S1" => PlayerAction::FirstSettlement(VertexCoord::from_notation(args)),
+        "IR" => PlayerAction::InitialRoadPlacement(EdgeCoord::from_notation(args)),
+        "SP" => PlayerAction::SecondPlacement,
+        "S2" => PlayerAction::SecondSettlement(VertexCoord::from_notation(args)),
+        "D" => PlayerAction::Discard(hand_from_notation(args)),
+        "S" => PlayerAction::PlaceSettlement(VertexCoord::from_notation(args)),
+        "C" => PlayerAction::PlaceCity(VertexCoord::from_notation(args)),
+        "R" => PlayerAction::PlaceRoad(EdgeCoord::from_notation(args)),
+        "RM" => PlayerAction::RobberMove(Coordinate2::from_notation(args)),
+        "RB" if chars.len() == 1 => PlayerAction::Rob(digit_from_notation(chars[0])),
+        "ST" if chars.len() == 2 => {
+            PlayerAction::RobberSteal(digit_from_notation(chars[0]), resource_from_notation(chars[1]))
+        }
+        "P" => port_from_notation(args),
+        "BD" => PlayerAction::BuyDev,
+        "PK" => PlayerAction::PlayKnight,
+        "PY" => PlayerAction::PlayYOP,
+        "PM" => PlayerAction::PlayMono,
+        "PR" => PlayerAction::PlayRoad,
+        "YP" if chars.len() == 2 => {
+            PlayerAction::YearOfPlenty(resource_from_notation(chars[0]), resource_from_notation(chars[1]))
+        }
+        "MO" if chars.len() == 1 => PlayerAction::Monopoly(resource_from_notation(chars[0])),
+        "FR" => PlayerAction::FreeRoad(EdgeCoord::from_notation(args)),
+        "PT" => PlayerAction::ProposeTrade(trade_from_notation(args)),
+        "AC" => PlayerAction::AnswerTrade(TradeResponse::Accept),
+        "RJ" => PlayerAction::AnswerTrade(TradeResponse::Reject),
+        "CO" => PlayerAction::AnswerTrade(TradeResponse::Counter(trade_from_notation(args))),
+        "TR" if !chars.is_empty() => {
+            let offer = trade_from_notation(&args[chars[0].len_utf8() ..]);
+            PlayerAction::Trade(digit_from_notation(chars[0]), offer.give, offer.receive)
+        }
+        "RO" => PlayerAction::Roll,
+        "PA" => PlayerAction::Pass,
+        _ => panic!("Invalid move notation: {}", str),
+    }
+}
+
+/**
+ * A port trade is written as the amount and resource given, then the amount and
+ * resource received, e.g. `P4B2W`
+ */
+fn port_from_notation(str: &str) -> PlayerAction {
+    let mut values = Vec::new();
+    let mut rest = str;
+    for _ in 0 .. 2 {
+        let i = match rest.find(|c: char| !c.is_ascii_digit()) {
+            Some(i) => i,
+            None => panic!("Invalid port notation: {}", str),
+        };
+        let res = rest[i ..].chars().next().unwrap();
+        match rest[.. i].parse::<u8>() {
+            Ok(amount) => values.push((amount, resource_from_notation(res))),
+            Err(_) => panic!("Invalid port notation: {}", str),
+        }
+        rest = &rest[i + res.len_utf8() ..];
+    }
+    if !rest.is_empty() {
+        panic!("Invalid port notation: {}", str);
+    }
+    PlayerAction::Port(values[0].0, values[0].1, values[1].0, values[1].1)
+}
+
+/**
+ * A trade is written as the cards given, then the cards received by the active
+ * player, e.g. `BB:W`
+ */
+fn trade_as_notation(offer: &TradeOffer) -> String {
+    format!("{}{}{}", hand_as_notation(offer.give), TRADE_SEPARATOR, hand_as_notation(offer.receive))
+}
+
+fn trade_from_notation(str: &str) -> TradeOffer {
+    match str.split_once(TRADE_SEPARATOR) {
+        Some((give, receive)) => TradeOffer::new(hand_from_notation(give), hand_from_notation(receive)),
+        None => panic!("Invalid trade notation: {}", str),
+    }
+}
+
+fn game_action_as_notation(action: &GameAction) -> String {
+    match action {
+        GameAction::GameStart => "GS".to_string(),
+        GameAction::FirstPlacement(player) => format!("FP{}", player),
+        GameAction::SecondPlacement(player) => format!("SP{}", player),
+        GameAction::PlacementFinished => "PF".to_string(),
+        GameAction::Roll(player, value) => format!("RO{},{}", player, value),
+        GameAction::DrawDev(player, card) => format!("DD{}{}", player, card.as_notation()),
+        GameAction::TurnDone => "TD".to_string(),
+        GameAction::Finished => "GF".to_string(),
+    }
+}
+
+fn game_action_from_notation(str: &str) -> GameAction {
+    if str.len() < 2 || !str.is_char_boundary(2) {
+        panic!("Invalid game action notation: {}", str);
+    }
+    let (code, args) = str.split_at(2);
+    let chars: Vec<char> = args.chars().collect();
+    match code {
+        "GS" => GameAction::GameStart,
+        "FP" if chars.len() == 1 => GameAction::FirstPlacement(digit_from_notation(chars[0])),
+        "SP" if chars.len() == 1 => GameAction::SecondPlacement(digit_from_notation(chars[0])),
+        "PF" => GameAction::PlacementFinished,
+        "RO" => match args.split_once(',').map(|(p, v)| (p.parse::<u8>(), v.parse::<u8>())) {
+            Some((Ok(player), Ok(value))) => GameAction::Roll(player, value),
+            _ => panic!("Invalid roll notation: {}", str),
+        },
+        "DD" if chars.len() == 2 => {
+            GameAction::DrawDev(digit_from_notation(chars[0]), DevCard::from_string(&chars[1].to_string()))
+        }
+        "TD" => GameAction::TurnDone,
+        "GF" => GameAction::Finished,
+        _ => panic!("Invalid game action notation: {}", str),
+    }
+}
+
+fn digit_from_notation(c: char) -> u8 {
+    match c.to_digit(10) {
+        Some(digit) => digit as u8,
+        None => panic!("Invalid digit in move notation: {}", c),
+    }
+}
+
+/**
+ * Resources use the letter of the tile producing them
+ */
+fn resource_as_notation(res: Resource) -> String {
+    res.as_notation()
+}
+
+fn resource_from_notation(c: char) -> Resource {
+    Resource::from_string(&c.to_string())
+}
+
+/**
+ * A hand of cards is written as one resource letter per card, e.g. `BBW`
+ */
+fn hand_as_notation(cards: Hand) -> String {
+    cards.to_string()
+}
+
+fn hand_from_notation(str: &str) -> Hand {
+    str.chars().fold(Hand::EMPTY, |cards, c| cards + Hand::of(resource_from_notation(c), 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{coordinate::{Coordinate2, EdgeCoord, VertexCoord}, tile::Resource};
+    use crate::game::{action::{GameAction, PlayerAction, RecordedAction}, devs::DevCard, hand::Hand, phase::TurnPhase, trade::{TradeOffer, TradeResponse}, Game};
+
+    const EXAMPLE: &str = "2/W2O3B10S8DL5O6B3S4W10O11W9B12S6L4B5S9L11W8/WTOTTLBST";
+
+    const DOCUMENT: &str = include_str!("../../../notation.md");
+
+    /**
+     * Get the first code block of the notation document after some text
+     */
+    fn documented_block(after: &str) -> &'static str {
+        let start = DOCUMENT.find(after).unwrap();
+        let block = &DOCUMENT[start ..];
+        let block = &block[block.find("```\n").unwrap() + 4 ..];
+        block[.. block.find("```").unwrap()].trim_end()
+    }
+
+    #[test]
+    fn documented_example_round_trip() {
+        let compact = documented_block("This can be written as:");
+        assert_eq!(compact, EXAMPLE);
+        assert_eq!(Game::from_notation(compact.to_string()).as_notation(), compact);
+
+        let spaced = documented_block("Example: A 2 player game before anybody");
+        assert_eq!(Game::from_notation(spaced.to_string()).as_notation(), compact);
+    }
+
+    #[test]
+    fn game_start_round_trip() {
+        let game = Game::from_notation(EXAMPLE.to_string());
+        assert_eq!(game.as_notation(), EXAMPLE);
+
+        for player_count in 2 ..= 6 {
+            let game = Game::random_from_seed(player_count, "notation".to_string());
+            let copy = Game::from_notation(game.as_notation());
+            assert_eq!(copy.as_notation(), game.as_notation());
+            assert_eq!(copy.board.robber, game.board.robber);
+        }
+    }
+
+    #[test]
+    fn move_round_trip() {
+        let vertex = VertexCoord::new(0, -2, 3);
+        let edge = EdgeCoord::new(0, -3, 3);
+        let cards = Hand::of(Resource::Brick, 2) + Hand::of(Resource::Wheat, 1);
+        let offer = TradeOffer::new(Hand::of(Resource::Ore, 1), Hand::of(Resource::Sheep, 2));
+        let actions = [
+            RecordedAction::Game(GameAction::GameStart),
+            RecordedAction::Game(GameAction::FirstPlacement(1)),
+            RecordedAction::Game(GameAction::SecondPlacement(2)),
+            RecordedAction::Game(GameAction::PlacementFinished),
+            RecordedAction::Game(GameAction::Roll(3, 12)),
+            RecordedAction::Game(GameAction::DrawDev(4, DevCard::Monopoly)),
+            RecordedAction::Game(GameAction::TurnDone),
+            RecordedAction::Game(GameAction::Finished),
+            RecordedAction::Player(1, PlayerAction::FirstPlacement),
+            RecordedAction::Player(1, PlayerAction::FirstSettlement(vertex)),
+            RecordedAction::Player(1, PlayerAction::InitialRoadPlacement(edge)),
+            RecordedAction::Player(1, PlayerAction::SecondPlacement),
+            RecordedAction::Player(1, PlayerAction::SecondSettlement(vertex)),
+            RecordedAction::Player(2, PlayerAction::Discard(cards)),
+            RecordedAction::Player(2, PlayerAction::PlaceSettlement(vertex)),
+            RecordedAction::Player(2, PlayerAction::PlaceCity(vertex)),
+            RecordedAction::Player(2, PlayerAction::PlaceRoad(edge)),
+            RecordedAction::Player(3, PlayerAction::RobberMove(Coordinate2::new(-1, 2))),
+            RecordedAction::Player(3, PlayerAction::Rob(1)),
+            RecordedAction::Player(3, PlayerAction::RobberSteal(1, Resource::Lumber)),
+            RecordedAction::Player(3, PlayerAction::Port(4, Resource::Brick, 1, Resource::Wheat)),
+            RecordedAction::Player(4, PlayerAction::BuyDev),
+            RecordedAction::Player(4, PlayerAction::PlayKnight),
+            RecordedAction::Player(4, PlayerAction::PlayYOP),
+            RecordedAction::Player(4, PlayerAction::PlayMono),
+            RecordedAction::Player(4, PlayerAction::PlayRoad),
+            RecordedAction::Player(4, PlayerAction::YearOfPlenty(Resource::Ore, Resource::Ore)),
+            RecordedAction::Player(4, PlayerAction::Monopoly(Resource::Sheep)),
+            RecordedAction::Player(4, PlayerAction::FreeRoad(edge)),
+            RecordedAction::Player(5, PlayerAction::ProposeTrade(offer)),
+            RecordedAction::Player(6, PlayerAction::AnswerTrade(TradeResponse::Accept)),
+            RecordedAction::Player(6, PlayerAction::AnswerTrade(TradeResponse::Reject)),
+            RecordedAction::Player(6, PlayerAction::AnswerTrade(TradeResponse::Counter(offer))),
+            RecordedAction::Player(5, PlayerAction::Trade(6, cards, Hand::of(Resource::Lumber, 1))),
+            RecordedAction::Player(5, PlayerAction::Roll),
+            RecordedAction::Player(5, PlayerAction::Pass),
+        ];
+        for action in actions {
+            assert_eq!(RecordedAction::from_notation(&action.as_notation()), action, "{}", action.as_notation());
+        }
+    }
+
+    #[test]
+    fn documented_moves_replay() {
+        let moves = "#GS; #FP1; 1FP; 1S1(0,-2,3); 1IRX-3,3; #FP2; 2FP; 2S1(1,1,-3); 2IRX1,-2; \
+            #SP2; 2SP; 2S2(-2,1,2); 2IRY1,-2; #SP1; 1SP; 1S2(3,-1,-1); 1IRZ2,-1; \
+            1RO; #RO1,8; 1PA; 2RO; #RO2,7; 2RM(0,-2); 2RB1; 2ST1O; 2PA";
+        let game = Game::from_notation(format!("{}/{}", EXAMPLE, moves));
+        assert_eq!(game.record_as_notation(), format!("{}/{}", EXAMPLE, moves.replace(' ', "")));
+        assert_eq!(*game.get_phase(), TurnPhase::PreRoll(1));
+        assert_eq!(game.get_hand(2)[Resource::Ore], 1);
+    }
+
+    #[test]
+    fn replay_reaches_the_same_position() {
+        for player_count in 2 ..= 6 {
+            let seed = format!("replay {}", player_count);
+            let mut game = Game::random_from_seed(player_count, seed.clone());
+            game.start();
+
+            let replay = Game::from_notation_with_seed(game.record_as_notation(), seed);
+            assert_eq!(replay.record_as_notation(), game.record_as_notation());
+            assert_eq!(replay.get_phase(), game.get_phase());
+            assert_eq!(replay.get_points(), game.get_points());
+            assert_eq!(replay.board.roads, game.board.roads);
+            assert_eq!(replay.board.buildings, game.board.buildings);
+            for player in 1 ..= player_count {
+                assert_eq!(replay.get_hand(player), game.get_hand(player));
+                assert_eq!(replay.get_devs(player), game.get_devs(player));
+            }
+        }
+    }
+
+}
@@ -0,0 +1,313 @@
+use crate::board::{coordinate::VertexCoord, tile::Resource};
+
+use super::{action::{GameAction, PlayerAction}, devs::DevHand, trade::TradeOffer, validate::IllegalAction, Game};
+
+/**
+ * The decision the game is waiting on. Phases with a player are waiting on that player,
+ * except the ones the game plays itself (the start of the placements and the dice roll).
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub enum TurnPhase {
+    GameStart,
+    FirstPlacement(u8), // Played by the game
+    FirstSettlement(u8),
+    FirstRoad(u8, VertexCoord), // Provide the player and the settlement the road goes next to
+    SecondPlacement(u8), // Played by the game
+    SecondSettlement(u8),
+    SecondRoad(u8, VertexCoord), // Provide the player and the settlement the road goes next to
+    PreRoll(u8),
+    Roll(u8), // Played by the game
+    Discard, // Every player who must discard decides at the same time
+    RobberMove(u8),
+    RobberSteal(u8),
+    Main(u8),
+    TradeAnswer(u8, TradeOffer), // Provide the player answering and the offer of the active player
+    RoadBuilding(u8),
+    YearOfPlenty(u8),
+    Monopoly(u8),
+    SpecialBuilding(u8),
+    Finished,
+}
+
+impl TurnPhase {
+
+    /**
+     * The player this phase belongs to, or 0 before and after the game and while
+     * several players are discarding
+     */
+    pub fn get_player(&self) -> u8 {
+        match *self {
+            TurnPhase::FirstPlacement(player)
+            | TurnPhase::FirstSettlement(player)
+            | TurnPhase::FirstRoad(player, _)
+            | TurnPhase::SecondPlacement(player)
+            | TurnPhase::SecondSettlement(player)
+            | TurnPhase::SecondRoad(player, _)
+            | TurnPhase::PreRoll(player)
+            | TurnPhase::Roll(player)
+            | TurnPhase::RobberMove(player)
+            | TurnPhase::RobberSteal(player)
+            | TurnPhase::Main(player)
+            | TurnPhase::TradeAnswer(player, _)
+            | TurnPhase::RoadBuilding(player)
+            | TurnPhase::YearOfPlenty(player)
+            | TurnPhase::Monopoly(player)
+            | TurnPhase::SpecialBuilding(player) => player,
+            TurnPhase::GameStart | TurnPhase::Discard | TurnPhase::Finished => 0,
+        }
+    }
+
+    /**
+     * Check if the game plays this phase itself, without a decision from a player
+     */
+    pub fn is_automatic(&self) -> bool {
+        matches!(self,
+            TurnPhase::GameStart
+            | TurnPhase::FirstPlacement(_)
+            | TurnPhase::SecondPlacement(_)
+            | TurnPhase::Roll(_)
+        )
+    }
+
+}
+
+impl Game {
+
+    /**
+     * The phase of the turn the game is in
+     */
+    pub fn get_phase(&self) -> &TurnPhase {
+        &self.phase
+    }
+
+    /**
+     * The player the game is waiting on, or 0 once the game is finished. While players
+     * are discarding this is the first player who still has to discard, though any of
+     * them may discard first.
+     */
+    pub fn get_current_player(&self) -> u8 {
+        match self.phase {
+            TurnPhase::Discard => (1 ..= self.player_count).find(|p| self.get_discard_count(*p) > 0).unwrap_or(0),
+            _ => self.phase.get_player(),
+        }
+    }
+
+    /**
+     * The number of cards a player still has to discard after a "7 out"
+     */
+    pub fn get_discard_count(&self, player: u8) -> u32 {
+        self.discards[(player - 1) as usize]
+    }
+
+    /**
+     * Every action the current player may play in the current phase
+     */
+    pub fn legal_actions(&self) -> Vec<PlayerAction> {
+        let mut vec = Vec::new();
+        match self.phase {
+            TurnPhase::FirstSettlement(_) => {
+                self.board.get_valid_starting_buiding_coords().for_each(|e| {
+                    vec.push(PlayerAction::FirstSettlement(e));
+                });
+            }
+            TurnPhase::SecondSettlement(_) => {
+                self.board.get_valid_starting_buiding_coords().for_each(|e| {
+                    vec.push(PlayerAction::SecondSettlement(e));
+                });
+            }
+            TurnPhase::FirstRoad(_, ref coord) | TurnPhase::SecondRoad(_, ref coord) => {
+                self.board.get_placement_roads_around(coord).for_each(|e| {
+                    vec.push(PlayerAction::InitialRoadPlacement(e));
+                });
+            }
+            TurnPhase::Discard => {
+                let player = self.get_current_player();
+                self.get_hand(player).sub_hands(self.get_discard_count(player)).into_iter().for_each(|e| {
+                    vec.push(PlayerAction::Discard(e));
+                });
+            }
+            TurnPhase::RobberMove(_) => {
+                self.get_robbable_tiles().for_each(|e| {
+                    vec.push(PlayerAction::RobberMove(e));
+                });
+            }
+            TurnPhase::RobberSteal(player) => {
+                let options = self.get_robbable_players(player, &self.board.robber);
+                options.iter().enumerate().filter(|(_, b)| **b).for_each(|(i, _)| {
+                    vec.push(PlayerAction::Rob((i + 1) as u8));
+                });
+            }
+            TurnPhase::PreRoll(player) => {
+                vec.append(&mut self.get_dev_actions(player));
+                vec.push(PlayerAction::Roll);
+            }
+            TurnPhase::Main(player) => {
+                vec.extend(self.trade_actions.iter().cloned());
+                vec.append(&mut self.get_turn_actions(player));
+            }
+            TurnPhase::TradeAnswer(player, ref offer) => {
+                vec.append(&mut self.get_trade_answer_actions(player, offer));
+            }
+            TurnPhase::SpecialBuilding(player) => {
+                vec.append(&mut self.get_turn_actions(player));
+            }
+            TurnPhase::RoadBuilding(player) => {
+                self.get_legal_road_locations(player).for_each(|e| {
+                    vec.push(PlayerAction::FreeRoad(e));
+                });
+            }
+            TurnPhase::YearOfPlenty(_) => {
+                vec.append(&mut self.get_year_of_plenty_actions());
+            }
+            TurnPhase::Monopoly(_) => {
+                for res in Resource::ALL {
+                    vec.push(PlayerAction::Monopoly(res));
+                }
+            }
+            TurnPhase::GameStart
+            | TurnPhase::FirstPlacement(_)
+            | TurnPhase::SecondPlacement(_)
+            | TurnPhase::Roll(_)
+            | TurnPhase::Finished => {}
+        }
+        vec
+    }
+
+    /**
+     * Play an action for the current player, then play the game up to the next decision.
+     * Actions that can not be played are rejected without changing the game.
+     *
+     * The outcome of the action is decided here: the card drawn from the deck, the card
+     * stolen by the robber and the dice.
+     */
+    pub fn apply(&mut self, action: PlayerAction) -> Result<(), IllegalAction> {
+        self.apply_as(self.get_current_player(), action)
+    }
+
+    /**
+     * Play an action for a player, who may be any of the players still discarding
+     */
+    pub fn apply_as(&mut self, player: u8, action: PlayerAction) -> Result<(), IllegalAction> {
+        self.validate(player, &action)?;
+
+        let step = self.begin_step();
+        self.apply_player_action(player, &action);
+        match action {
+            PlayerAction::BuyDev => {
+                // Draw the top card of the deck
+                let card = self.bank.peek_dev().unwrap();
+                self.apply_game_action(&GameAction::DrawDev(player, card));
+            }
+            PlayerAction::Rob(other) => {
+                let res = self.steal(self.get_hand(other));
+                self.apply_player_action(player, &PlayerAction::RobberSteal(other, res));
+            }
+            _ => {}
+        }
+        self.advance();
+        self.push_step(step);
+        Ok(())
+    }
+
+    /**
+     * Play the phases that need no decision until a player has to act. A new game
+     * starts here, before the first placement.
+     */
+    pub(super) fn advance(&mut self) {
+        while self.phase.is_automatic() {
+            match self.phase {
+                TurnPhase::GameStart => {
+                    self.apply_game_action(&GameAction::GameStart);
+                }
+                TurnPhase::FirstPlacement(player) => {
+                    self.apply_game_action(&GameAction::FirstPlacement(player));
+                    self.apply_player_action(player, &PlayerAction::FirstPlacement);
+                }
+                TurnPhase::SecondPlacement(player) => {
+                    self.apply_game_action(&GameAction::SecondPlacement(player));
+                    self.apply_player_action(player, &PlayerAction::SecondPlacement);
+                }
+                TurnPhase::Roll(player) => {
+                    let value = self.roll();
+                    self.apply_game_action(&GameAction::Roll(player, value));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /**
+     * The next player in turn order
+     */
+    pub(super) fn next_player(&self, player: u8) -> u8 {
+        player % self.player_count + 1
+    }
+
+    /**
+     * The phase after the current player passes. In games with a Special Building Phase
+     * every other player may build before the next player rolls.
+     */
+    pub(super) fn get_phase_after_pass(&self) -> TurnPhase {
+        let next = match self.phase {
+            TurnPhase::Main(player) | TurnPhase::SpecialBuilding(player) => self.next_player(player),
+            _ => panic!("Can not pass in {:?}", self.phase),
+        };
+        if next == self.turn_player {
+            TurnPhase::PreRoll(self.next_player(next))
+        } else if self.has_special_building_phase() {
+            TurnPhase::SpecialBuilding(next)
+        } else {
+            TurnPhase::PreRoll(next)
+        }
+    }
+
+    /**
+     * Start the turn of a player, who may play a development card before rolling
+     */
+    pub(super) fn start_turn(&mut self, player: u8) -> TurnPhase {
+        self.turn_player = player;
+        self.turn_count += 1;
+        self.has_rolled = false;
+        self.dev_played = false;
+        self.bought_devs = DevHand::EMPTY;
+        TurnPhase::PreRoll(player)
+    }
+
+    /**
+     * The phase after a development card or the robber is played, which is before
+     * the roll if the player has not rolled yet
+     */
+    pub(super) fn get_phase_after_card(&self, player: u8) -> TurnPhase {
+        if self.has_rolled {
+            TurnPhase::Main(player)
+        } else {
+            TurnPhase::PreRoll(player)
+        }
+    }
+
+    /**
+     * After a "7 out" every player with too many cards must discard half of them,
+     * rounded down. The robber is moved once nobody has to discard.
+     */
+    pub(super) fn start_discards(&mut self) -> TurnPhase {
+        for p in 1 ..= self.player_count {
+            let size = self.get_hand(p).size();
+            if size > self.max_cards.into() {
+                self.discards[(p - 1) as usize] = size / 2;
+            }
+        }
+        self.get_phase_after_discard()
+    }
+
+    /**
+     * Wait until every player has discarded before moving the robber
+     */
+    pub(super) fn get_phase_after_discard(&self) -> TurnPhase {
+        if self.discards.iter().any(|e| *e > 0) {
+            TurnPhase::Discard
+        } else {
+            TurnPhase::RobberMove(self.turn_player)
+        }
+    }
+
+}
@@ -0,0 +1,85 @@
+use super::{undo::Change, Game};
+
+// Pieces each player starts the game with
+pub const ROAD_COUNT: u8 = 15;
+pub const SETTLEMENT_COUNT: u8 = 5;
+pub const CITY_COUNT: u8 = 4;
+
+/**
+ * The pieces a player has left to build with
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Pieces {
+    pub roads: u8,
+    pub settlements: u8,
+    pub cities: u8,
+}
+
+impl Pieces {
+
+    /**
+     * The full supply of pieces at the start of the game
+     */
+    pub fn new() -> Self {
+        Pieces {
+            roads: ROAD_COUNT,
+            settlements: SETTLEMENT_COUNT,
+            cities: CITY_COUNT,
+        }
+    }
+
+}
+
+impl Default for Pieces {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Game {
+
+    /**
+     * The pieces a player has left to build with
+     */
+    pub fn get_pieces(&self, player: u8) -> Pieces {
+        self.pieces[(player - 1) as usize]
+    }
+
+    /**
+     * Take a road from a player's supply
+     */
+    pub(super) fn use_road(&mut self, player: u8) {
+        self.log(Change::Pieces(player, self.get_pieces(player)));
+        let pieces = &mut self.pieces[(player - 1) as usize];
+        if pieces.roads == 0 {
+            panic!("Player {} has no roads left", player);
+        }
+        pieces.roads -= 1;
+    }
+
+    /**
+     * Take a settlement from a player's supply
+     */
+    pub(super) fn use_settlement(&mut self, player: u8) {
+        self.log(Change::Pieces(player, self.get_pieces(player)));
+        let pieces = &mut self.pieces[(player - 1) as usize];
+        if pieces.settlements == 0 {
+            panic!("Player {} has no settlements left", player);
+        }
+        pieces.settlements -= 1;
+    }
+
+    /**
+     * Take a city from a player's supply. The settlement it replaces goes back to the supply.
+     */
+    pub(super) fn use_city(&mut self, player: u8) {
+        self.log(Change::Pieces(player, self.get_pieces(player)));
+        let pieces = &mut self.pieces[(player - 1) as usize];
+        if pieces.cities == 0 {
+            panic!("Player {} has no cities left", player);
+        }
+        pieces.cities -= 1;
+        pieces.settlements += 1;
+    }
+
+}
@@ -0,0 +1,214 @@
+use crate::board::tile::Resource;
+
+use super::{action::PlayerAction, hand::Hand, phase::TurnPhase, Game};
+
+/**
+ * A trade between the active player and another player, from the point of view of
+ * the active player.
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TradeOffer {
+    pub give: Hand,
+    pub receive: Hand,
+}
+
+/**
+ * The answer of another player to a trade offer
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TradeResponse {
+    Accept,
+    Reject,
+    Counter(TradeOffer), // A different trade, still from the point of view of the active player
+}
+
+impl TradeOffer {
+
+    pub fn new(give: Hand, receive: Hand) -> Self {
+        TradeOffer { give, receive }
+    }
+
+    /**
+     * Both sides of a trade must have cards
+     */
+    pub fn is_valid(&self) -> bool {
+        !self.give.is_empty() && !self.receive.is_empty()
+    }
+
+}
+
+impl Game {
+
+    /**
+     * Get the trades the active player may offer the other players. Only offers of one
+     * resource for another at 1:1, 2:1 and 1:2 are listed, though any offer the player
+     * can pay for may be played.
+     */
+    pub(super) fn get_trade_offer_actions(&self, player: u8) -> Vec<PlayerAction> {
+        let hand = self.get_hand(player);
+        let mut vec = Vec::new();
+        for give in Resource::ALL.into_iter().filter(|res| hand[*res] > 0) {
+            for receive in Resource::ALL.into_iter().filter(|res| *res != give) {
+                for (give_count, receive_count) in [(1, 1), (2, 1), (1, 2)] {
+                    if hand[give] >= give_count {
+                        let offer = TradeOffer::new(Hand::of(give, give_count), Hand::of(receive, receive_count));
+                        vec.push(PlayerAction::ProposeTrade(offer));
+                    }
+                }
+            }
+        }
+        vec
+    }
+
+    /**
+     * Get the answers a player may give to a trade offer. Counter offers are not listed,
+     * though any counter offer both players can pay for may be played.
+     */
+    pub(super) fn get_trade_answer_actions(&self, player: u8, offer: &TradeOffer) -> Vec<PlayerAction> {
+        let mut vec = Vec::new();
+        if self.get_hand(player).has(offer.receive) {
+            vec.push(PlayerAction::AnswerTrade(TradeResponse::Accept));
+        }
+        vec.push(PlayerAction::AnswerTrade(TradeResponse::Reject));
+        vec
+    }
+
+    /**
+     * Record the answer of a player to the trade on offer, then ask the next player.
+     * Once every other player has answered, the active player picks one of the trades
+     * agreed to or carries on with the turn.
+     */
+    pub(super) fn answer_trade(&mut self, player: u8, response: TradeResponse) {
+        let offer = match self.phase {
+            TurnPhase::TradeAnswer(_, offer) => offer,
+            _ => panic!("Can not answer a trade in {:?}", self.phase),
+        };
+
+        let trade = match response {
+            TradeResponse::Accept => Some(offer),
+            TradeResponse::Counter(counter) => Some(counter),
+            TradeResponse::Reject => None,
+        };
+        if let Some(trade) = trade {
+            self.trade_actions.push(PlayerAction::Trade(player, trade.give, trade.receive));
+        }
+
+        let next = self.next_player(player);
+        self.phase = if next == self.turn_player {
+            TurnPhase::Main(next)
+        } else {
+            TurnPhase::TradeAnswer(next, offer)
+        };
+    }
+
+    /**
+     * Check if the active player can make a trade with their hand
+     */
+    fn can_trade(&self, player: u8, offer: &TradeOffer) -> bool {
+        self.phase == TurnPhase::Main(player)
+            && offer.is_valid()
+            && self.get_hand(player).has(offer.give)
+    }
+
+    /**
+     * Swap the cards of a trade between the active player and another player. Both
+     * hands are checked before either is changed.
+     */
+    pub(super) fn apply_trade(&mut self, player: u8, other: u8, offer: &TradeOffer) {
+        if player == other || !self.can_trade(player, offer) || !self.get_hand(other).has(offer.receive) {
+            panic!("Invalid trade between player {} and player {}: {:?}", player, other, offer);
+        }
+
+        self.set_hand(player, self.get_hand(player) - offer.give + offer.receive);
+        self.set_hand(other, self.get_hand(other) - offer.receive + offer.give);
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::tile::Resource;
+    use crate::game::{action::PlayerAction, hand::Hand, phase::TurnPhase, validate::IllegalAction, Game};
+
+    use super::{TradeOffer, TradeResponse};
+
+    /**
+     * A 3 player game on the first player's turn, after the roll
+     */
+    fn game_with_hands(hands: [Hand; 3]) -> Game {
+        let mut game = Game::random_from_seed(3, "trade".to_string());
+        for (i, hand) in hands.into_iter().enumerate() {
+            game.set_hand(i as u8 + 1, hand);
+        }
+        game.turn_player = 1;
+        game.has_rolled = true;
+        game.phase = TurnPhase::Main(1);
+        game
+    }
+
+    #[test]
+    fn bundle_offers_are_listed() {
+        let game = game_with_hands([Hand::of(Resource::Wheat, 2), Hand::EMPTY, Hand::EMPTY]);
+        let actions = game.legal_actions();
+        for (give, receive) in [(1, 1), (2, 1), (1, 2)] {
+            let offer = TradeOffer::new(Hand::of(Resource::Wheat, give), Hand::of(Resource::Ore, receive));
+            assert!(actions.contains(&PlayerAction::ProposeTrade(offer)));
+        }
+
+        let game = game_with_hands([Hand::of(Resource::Wheat, 1), Hand::EMPTY, Hand::EMPTY]);
+        let offer = TradeOffer::new(Hand::of(Resource::Wheat, 2), Hand::of(Resource::Ore, 1));
+        assert!(!game.legal_actions().contains(&PlayerAction::ProposeTrade(offer)));
+    }
+
+    #[test]
+    fn bundle_trade_swaps_the_cards() {
+        let wheat = Hand::of(Resource::Wheat, 2);
+        let ore = Hand::of(Resource::Ore, 1) + Hand::of(Resource::Sheep, 1);
+        let mut game = game_with_hands([wheat + Hand::of(Resource::Brick, 1), ore, Hand::EMPTY]);
+        let offer = TradeOffer::new(wheat, ore);
+
+        game.apply(PlayerAction::ProposeTrade(offer)).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::TradeAnswer(2, offer));
+        game.apply(PlayerAction::AnswerTrade(TradeResponse::Accept)).unwrap();
+
+        // The third player can not pay for the trade
+        assert_eq!(game.apply(PlayerAction::AnswerTrade(TradeResponse::Accept)), Err(IllegalAction::InsufficientResources));
+        game.apply(PlayerAction::AnswerTrade(TradeResponse::Reject)).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::Main(1));
+
+        game.apply(PlayerAction::Trade(2, wheat, ore)).unwrap();
+        assert_eq!(game.get_hand(1), ore + Hand::of(Resource::Brick, 1));
+        assert_eq!(game.get_hand(2), wheat);
+    }
+
+    #[test]
+    fn counter_offer_is_traded_instead() {
+        let mut game = game_with_hands([Hand::of(Resource::Wheat, 3), Hand::of(Resource::Ore, 1), Hand::EMPTY]);
+        let offer = TradeOffer::new(Hand::of(Resource::Wheat, 1), Hand::of(Resource::Ore, 1));
+        let counter = TradeOffer::new(Hand::of(Resource::Wheat, 3), Hand::of(Resource::Ore, 1));
+
+        game.apply(PlayerAction::ProposeTrade(offer)).unwrap();
+        game.apply(PlayerAction::AnswerTrade(TradeResponse::Counter(counter))).unwrap();
+        game.apply(PlayerAction::AnswerTrade(TradeResponse::Reject)).unwrap();
+
+        // Only the agreed trade can be played
+        assert_eq!(game.apply(PlayerAction::Trade(2, offer.give, offer.receive)), Err(IllegalAction::TradeNotAgreed));
+        game.apply(PlayerAction::Trade(2, counter.give, counter.receive)).unwrap();
+        assert_eq!(game.get_hand(1), Hand::of(Resource::Ore, 1));
+        assert_eq!(game.get_hand(2), Hand::of(Resource::Wheat, 3));
+    }
+
+    #[test]
+    fn only_the_active_player_offers_trades_with_cards() {
+        let mut game = game_with_hands([Hand::of(Resource::Wheat, 1), Hand::of(Resource::Ore, 1), Hand::EMPTY]);
+        let offer = TradeOffer::new(Hand::of(Resource::Ore, 1), Hand::of(Resource::Wheat, 1));
+        assert_eq!(game.apply_as(2, PlayerAction::ProposeTrade(offer)), Err(IllegalAction::WrongPlayer));
+
+        let empty = TradeOffer::new(Hand::of(Resource::Wheat, 1), Hand::EMPTY);
+        assert_eq!(game.apply(PlayerAction::ProposeTrade(empty)), Err(IllegalAction::InvalidTrade));
+        let gift = TradeOffer::new(Hand::EMPTY, Hand::of(Resource::Ore, 1));
+        assert_eq!(game.apply(PlayerAction::ProposeTrade(gift)), Err(IllegalAction::InvalidTrade));
+        assert_eq!(game.apply(PlayerAction::ProposeTrade(offer)), Err(IllegalAction::InsufficientResources));
+    }
+
+}
@@ -0,0 +1,282 @@
+use rand_pcg::Pcg64;
+
+use crate::board::{building::Building, coordinate::{Coordinate2, EdgeCoord, VertexCoord}};
+
+use super::{action::{PlayerAction, RecordedAction}, devs::{DevCard, DevHand}, hand::Hand, phase::TurnPhase, pieces::Pieces, Game, MAX_PLAYERS};
+
+/**
+ * A single change to the game, holding the value it replaced
+ */
+#[derive(Clone, PartialEq, Debug)]
+pub(super) enum Change {
+    Hand(u8, Hand), // Provide the player and their previous hand
+    Devs(u8, DevHand), // Provide the player and their previous development cards
+    PlayedKnights(u8, u8), // Provide the player and their previous army size
+    Pieces(u8, Pieces), // Provide the player and their previous supply
+    Bank(Hand), // Provide the previous resources of the bank
+    DevDrawn(usize, DevCard), // Provide where the card was in the deck
+    Building(VertexCoord, u8), // Provide the vertex and the previous encoded building
+    Road(EdgeCoord, u8), // Provide the edge and the previous road
+    Robber(Coordinate2), // Provide the previous robber tile
+}
+
+/**
+ * Everything needed to take back one decision: the changes it made, in order, and the
+ * rest of the game state as it was before
+ */
+pub(super) struct UndoStep {
+    changes: Vec<Change>,
+    record_len: usize,
+    phase: TurnPhase,
+    turn_player: u8,
+    turn_count: u32,
+    has_rolled: bool,
+    dev_played: bool,
+    bought_devs: DevHand,
+    free_roads: u8,
+    discards: [u32; MAX_PLAYERS],
+    largest_army_player: u8,
+    longest_road_player: u8,
+    trade_actions: Vec<PlayerAction>,
+    rng: Pcg64,
+}
+
+/**
+ * A decision that was taken back. Replaying its actions plays it again exactly,
+ * without asking the agents or the dice.
+ */
+pub(super) struct RedoStep {
+    actions: Vec<RecordedAction>,
+    trade_actions: Vec<PlayerAction>,
+    rng: Pcg64,
+}
+
+impl Game {
+
+    /**
+     * Take back the last decision, and every action the game played after it.
+     * Returns false if there is nothing to undo.
+     */
+    pub fn undo(&mut self) -> bool {
+        let step = match self.undo_steps.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        self.redo_steps.push(RedoStep {
+            actions: self.record.split_off(step.record_len),
+            trade_actions: std::mem::take(&mut self.trade_actions),
+            rng: self.rng.clone(),
+        });
+
+        step.changes.into_iter().rev().for_each(|change| self.revert(change));
+        self.phase = step.phase;
+        self.turn_player = step.turn_player;
+        self.turn_count = step.turn_count;
+        self.has_rolled = step.has_rolled;
+        self.dev_played = step.dev_played;
+        self.bought_devs = step.bought_devs;
+        self.free_roads = step.free_roads;
+        self.discards = step.discards;
+        self.largest_army_player = step.largest_army_player;
+        self.longest_road_player = step.longest_road_player;
+        self.trade_actions = step.trade_actions;
+        self.rng = step.rng;
+        true
+    }
+
+    /**
+     * Play the last decision taken back again. Returns false if there is nothing to redo.
+     */
+    pub fn redo(&mut self) -> bool {
+        let step = match self.redo_steps.pop() {
+            Some(step) => step,
+            None => return false,
+        };
+
+        let undo_step = self.begin_step();
+        step.actions.iter().for_each(|e| self.apply_recorded_action(e));
+        self.trade_actions = step.trade_actions;
+        self.rng = step.rng;
+        self.undo_steps.push(undo_step.finish(&mut self.changes));
+        true
+    }
+
+    /**
+     * Start collecting the changes of a new decision
+     */
+    pub(super) fn begin_step(&mut self) -> UndoStep {
+        self.changes.clear();
+        UndoStep {
+            changes: Vec::new(),
+            record_len: self.record.len(),
+            phase: self.phase.clone(),
+            turn_player: self.turn_player,
+            turn_count: self.turn_count,
+            has_rolled: self.has_rolled,
+            dev_played: self.dev_played,
+            bought_devs: self.bought_devs,
+            free_roads: self.free_roads,
+            discards: self.discards,
+            largest_army_player: self.largest_army_player,
+            longest_road_player: self.longest_road_player,
+            trade_actions: self.trade_actions.clone(),
+            rng: self.rng.clone(),
+        }
+    }
+
+    /**
+     * Add the step of a new decision. Playing a new decision drops the decisions
+     * that were taken back.
+     */
+    pub(super) fn push_step(&mut self, step: UndoStep) {
+        self.undo_steps.push(step.finish(&mut self.changes));
+        self.redo_steps.clear();
+    }
+
+    /**
+     * Keep track of a change so it can be undone
+     */
+    pub(super) fn log(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    /**
+     * Put back the value replaced by a change
+     */
+    fn revert(&mut self, change: Change) {
+        match change {
+            Change::Hand(player, hand) => self.hands[(player - 1) as usize] = hand,
+            Change::Devs(player, devs) => self.devs[(player - 1) as usize] = devs,
+            Change::PlayedKnights(player, count) => self.played_knights[(player - 1) as usize] = count,
+            Change::Pieces(player, pieces) => self.pieces[(player - 1) as usize] = pieces,
+            Change::Bank(resources) => self.bank.set_resources(resources),
+            Change::DevDrawn(i, card) => self.bank.return_dev(i, card),
+            Change::Building(ref coord, building) => {
+                let (building, player) = Building::from_u8(building);
+                self.board.set_building(coord, building, player);
+            }
+            Change::Road(ref coord, road) => self.board.set_road(coord, road),
+            Change::Robber(coord) => self.board.robber = coord,
+        }
+    }
+
+    /**
+     * Set a building on the board
+     */
+    pub(super) fn place_building(&mut self, coord: &VertexCoord, building: Building, player: u8) {
+        self.log(Change::Building(*coord, self.board.get_building(coord)));
+        self.board.set_building(coord, building, player);
+    }
+
+    /**
+     * Set a road on the board
+     */
+    pub(super) fn place_road(&mut self, coord: &EdgeCoord, player: u8) {
+        self.log(Change::Road(*coord, self.board.get_road(coord)));
+        self.board.set_road(coord, player);
+    }
+
+    /**
+     * Move the robber to a tile
+     */
+    pub(super) fn place_robber(&mut self, coord: &Coordinate2) {
+        self.log(Change::Robber(self.board.robber.clone()));
+        self.board.robber = coord.clone();
+    }
+
+}
+
+impl UndoStep {
+
+    fn finish(mut self, changes: &mut Vec<Change>) -> UndoStep {
+        self.changes = std::mem::take(changes);
+        self
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::game::{action::PlayerAction, Game};
+
+    /**
+     * Everything about a position that undo and redo must restore
+     */
+    fn snapshot(game: &Game) -> String {
+        let mut str = format!(
+            "{} {:?} {} {} {} {:?} {:?} {:?} {:?} {:?}",
+            game.record_as_notation(), game.phase, game.turn_player, game.turn_count,
+            game.largest_army_player, game.longest_road_player, game.bank.get_resources(),
+            game.bank.get_dev_count(), game.trade_actions, game.legal_actions(),
+        );
+        str += &format!(" {:?} {:?} {:?}", game.board.roads, game.board.buildings, game.board.robber);
+        for player in 1 ..= game.player_count {
+            str += &format!(
+                " {} {} {:?} {} {}",
+                game.get_hand(player), game.get_devs(player), game.get_pieces(player),
+                game.get_army_size(player), game.get_road_length(player),
+            );
+        }
+        str
+    }
+
+    #[test]
+    fn undo_to_start_and_redo_to_end() {
+        for player_count in 2 ..= 6 {
+            let seed = format!("undo {}", player_count);
+            let start = snapshot(&Game::random_from_seed(player_count, seed.clone()));
+            let mut game = Game::random_from_seed(player_count, seed);
+            game.start();
+            let end = snapshot(&game);
+
+            while game.undo() {}
+            assert_eq!(snapshot(&game), start);
+
+            while game.redo() {}
+            assert_eq!(snapshot(&game), end);
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_each_step() {
+        let mut game = Game::random_from_seed(4, "undo steps".to_string());
+        for _ in 0 .. 500 {
+            let before = snapshot(&game);
+            let player = game.get_current_player();
+            let action = game.call_player(player);
+            if game.apply_as(player, action).is_err() {
+                break;
+            }
+            let after = snapshot(&game);
+
+            assert!(game.undo());
+            assert_eq!(snapshot(&game), before);
+            assert!(game.redo());
+            assert_eq!(snapshot(&game), after);
+        }
+    }
+
+    #[test]
+    fn new_action_clears_redo() {
+        let mut game = Game::random_from_seed(2, "undo redo".to_string());
+        let action = game.legal_actions()[0].clone();
+        game.apply(action).unwrap();
+        assert!(game.undo());
+
+        let action = game.legal_actions()[1].clone();
+        game.apply(action).unwrap();
+        assert!(!game.redo());
+        assert!(game.undo());
+        assert!(!game.undo());
+    }
+
+    #[test]
+    fn nothing_to_undo_in_a_new_game() {
+        let mut game = Game::random_from_seed(3, "undo".to_string());
+        assert!(!game.undo());
+        assert!(!game.redo());
+        assert_eq!(game.legal_actions().first().map(|e| matches!(e, PlayerAction::FirstSettlement(_))), Some(true));
+    }
+
+}
@@ -0,0 +1,560 @@
+use std::fmt::Display;
+
+use crate::board::{building::{Building, PLAYER_VALUE_MASK}, coordinate::{EdgeCoord, VertexCoord}, tile::Resource, topology::{EdgeId, VertexId}};
+
+use super::{action::PlayerAction, devs::DevCard, hand::{self, Hand}, phase::TurnPhase, trade::{TradeOffer, TradeResponse}, Game};
+
+/**
+ * The reason an action can not be played
+ */
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IllegalAction {
+    WrongPlayer, // The game is waiting on another player
+    WrongPhase, // The action is not played in this phase of the turn
+    InvalidCoordinate, // The coordinate is not on the board
+    Occupied, // There is already a piece there
+    DistanceRule, // There is a building on a neighboring vertex
+    NotConnected, // The piece is not connected to the player's roads or settlement
+    NotOwnSettlement, // A city must replace one of the player's settlements
+    InsufficientResources, // The player does not have the cards
+    NoPiecesLeft, // The player has none of the piece left to build with
+    NoDevCard, // The player does not have the development card
+    DevCardAlreadyPlayed, // A development card was already played this turn
+    DevCardBoughtThisTurn, // The development card was bought this turn
+    BankEmpty, // The bank does not have the cards, or the deck is empty
+    InvalidDiscard, // The discard is not half of the player's hand
+    InvalidTrade, // The trade is not at the player's rate, or has no cards
+    TradeNotAgreed, // The other player did not agree to the trade
+    RobberNotMoved, // The robber must move to a different tile
+    CannotRob, // The player is not next to the robber, or has no cards
+}
+
+impl Display for IllegalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let str = match self {
+            IllegalAction::WrongPlayer => "it is not this player's decision",
+            IllegalAction::WrongPhase => "the action can not be played in this phase",
+            IllegalAction::InvalidCoordinate => "the coordinate is not on the board",
+            IllegalAction::Occupied => "there is already a piece there",
+            IllegalAction::DistanceRule => "a settlement can not be next to another building",
+            IllegalAction::NotConnected => "the piece is not connected to the player's pieces",
+            IllegalAction::NotOwnSettlement => "a city must replace one of the player's settlements",
+            IllegalAction::InsufficientResources => "the player does not have the cards",
+            IllegalAction::NoPiecesLeft => "the player has no pieces left",
+            IllegalAction::NoDevCard => "the player does not have the development card",
+            IllegalAction::DevCardAlreadyPlayed => "only one development card may be played a turn",
+            IllegalAction::DevCardBoughtThisTurn => "a development card can not be played on the turn it was bought",
+            IllegalAction::BankEmpty => "the bank does not have the cards",
+            IllegalAction::InvalidDiscard => "the discard must be half of the hand",
+            IllegalAction::InvalidTrade => "the trade is not at the player's rate",
+            IllegalAction::TradeNotAgreed => "the other player did not agree to the trade",
+            IllegalAction::RobberNotMoved => "the robber must be moved to another tile",
+            IllegalAction::CannotRob => "the player can not be robbed",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+impl std::error::Error for IllegalAction {}
+
+impl Game {
+
+    /**
+     * Check if a player may play an action in the current phase, with their hand and the
+     * pieces on the board
+     */
+    pub fn validate(&self, player: u8, action: &PlayerAction) -> Result<(), IllegalAction> {
+        // Any player with cards to discard may discard first
+        let waiting = match self.phase {
+            TurnPhase::Discard => (1 ..= self.player_count).contains(&player) && self.get_discard_count(player) > 0,
+            _ => player == self.get_current_player(),
+        };
+        if !waiting {
+            return Err(IllegalAction::WrongPlayer)
+        }
+
+        match (&self.phase, action) {
+            (TurnPhase::FirstSettlement(_), PlayerAction::FirstSettlement(coord))
+            | (TurnPhase::SecondSettlement(_), PlayerAction::SecondSettlement(coord)) => {
+                self.check_building_location(coord)
+            }
+            (TurnPhase::FirstRoad(_, settlement), PlayerAction::InitialRoadPlacement(coord))
+            | (TurnPhase::SecondRoad(_, settlement), PlayerAction::InitialRoadPlacement(coord)) => {
+                self.check_edge(coord)?;
+                if !self.board.get_placement_roads_around(settlement).any(|e| e == *coord) {
+                    return Err(IllegalAction::NotConnected)
+                }
+                Ok(())
+            }
+            (TurnPhase::Discard, PlayerAction::Discard(cards)) => {
+                if !self.get_hand(player).has(*cards) {
+                    return Err(IllegalAction::InsufficientResources)
+                }
+                if cards.size() != self.get_discard_count(player) {
+                    return Err(IllegalAction::InvalidDiscard)
+                }
+                Ok(())
+            }
+            (TurnPhase::RobberMove(_), PlayerAction::RobberMove(coord)) => {
+                if !self.board.is_valid_tile(coord) {
+                    return Err(IllegalAction::InvalidCoordinate)
+                }
+                if *coord == self.board.robber {
+                    return Err(IllegalAction::RobberNotMoved)
+                }
+                Ok(())
+            }
+            (TurnPhase::RobberSteal(_), PlayerAction::Rob(other)) => {
+                let options = self.get_robbable_players(player, &self.board.robber);
+                match (*other as usize).checked_sub(1).and_then(|i| options.get(i)) {
+                    Some(true) => Ok(()),
+                    _ => Err(IllegalAction::CannotRob),
+                }
+            }
+            (TurnPhase::Main(_) | TurnPhase::SpecialBuilding(_), PlayerAction::PlaceRoad(coord)) => {
+                self.check_road(player, coord)?;
+                self.check_cards(player, hand::ROAD_RECIPE)
+            }
+            (TurnPhase::Main(_) | TurnPhase::SpecialBuilding(_), PlayerAction::PlaceSettlement(coord)) => {
+                self.check_settlement(player, coord)?;
+                self.check_cards(player, hand::SETTLE_RECIPE)
+            }
+            (TurnPhase::Main(_) | TurnPhase::SpecialBuilding(_), PlayerAction::PlaceCity(coord)) => {
+                self.check_city(player, coord)?;
+                self.check_cards(player, hand::CITY_RECIPE)
+            }
+            (TurnPhase::Main(_) | TurnPhase::SpecialBuilding(_), PlayerAction::BuyDev) => {
+                self.check_cards(player, hand::DEV_RECIPE)?;
+                if self.bank.get_dev_count() == 0 {
+                    return Err(IllegalAction::BankEmpty)
+                }
+                Ok(())
+            }
+            (TurnPhase::Main(_) | TurnPhase::SpecialBuilding(_), PlayerAction::Pass) => Ok(()),
+            (TurnPhase::PreRoll(_), PlayerAction::Roll) => Ok(()),
+            (TurnPhase::PreRoll(_) | TurnPhase::Main(_), PlayerAction::PlayKnight) => {
+                self.check_dev(player, DevCard::Knight)
+            }
+            (TurnPhase::PreRoll(_) | TurnPhase::Main(_), PlayerAction::PlayYOP) => {
+                self.check_dev(player, DevCard::YearOfPlenty)?;
+                if self.get_year_of_plenty_actions().is_empty() {
+                    return Err(IllegalAction::BankEmpty)
+                }
+                Ok(())
+            }
+            (TurnPhase::PreRoll(_) | TurnPhase::Main(_), PlayerAction::PlayMono) => {
+                self.check_dev(player, DevCard::Monopoly)
+            }
+            (TurnPhase::PreRoll(_) | TurnPhase::Main(_), PlayerAction::PlayRoad) => {
+                self.check_dev(player, DevCard::RoadBuilding)?;
+                if self.get_pieces(player).roads == 0 {
+                    return Err(IllegalAction::NoPiecesLeft)
+                }
+                if self.get_legal_road_locations(player).next().is_none() {
+                    return Err(IllegalAction::NotConnected)
+                }
+                Ok(())
+            }
+            (TurnPhase::Main(_), PlayerAction::ProposeTrade(offer)) => {
+                if !offer.is_valid() {
+                    return Err(IllegalAction::InvalidTrade)
+                }
+                self.check_cards(player, offer.give)
+            }
+            (TurnPhase::TradeAnswer(_, offer), PlayerAction::AnswerTrade(response)) => {
+                self.check_trade_answer(player, offer, response)
+            }
+            (TurnPhase::Main(_), PlayerAction::Trade(_, _, _)) => {
+                if !self.trade_actions.contains(action) {
+                    return Err(IllegalAction::TradeNotAgreed)
+                }
+                Ok(())
+            }
+            (TurnPhase::Main(_), PlayerAction::Port(give, from, receive, to)) => {
+                self.check_port(player, *give, *from, *receive, *to)
+            }
+            (TurnPhase::RoadBuilding(_), PlayerAction::FreeRoad(coord)) => {
+                self.check_road(player, coord)
+            }
+            (TurnPhase::YearOfPlenty(_), PlayerAction::YearOfPlenty(a, b)) => {
+                if !self.bank.has(Hand::of(*a, 1) + Hand::of(*b, 1)) {
+                    return Err(IllegalAction::BankEmpty)
+                }
+                Ok(())
+            }
+            (TurnPhase::Monopoly(_), PlayerAction::Monopoly(_)) => Ok(()),
+            _ => Err(IllegalAction::WrongPhase),
+        }
+    }
+
+    /**
+     * Check if a road can be built on an edge, next to one of the player's roads
+     */
+    pub(super) fn check_road(&self, player: u8, coord: &EdgeCoord) -> Result<(), IllegalAction> {
+        if self.get_pieces(player).roads == 0 {
+            return Err(IllegalAction::NoPiecesLeft)
+        }
+        self.check_road_at(player, self.get_edge_id(coord)?)
+    }
+
+    /**
+     * Check if a road can be built on the edge with an id, not counting the player's pieces
+     */
+    pub(super) fn check_road_at(&self, player: u8, edge: EdgeId) -> Result<(), IllegalAction> {
+        if self.board.get_road_by_id(edge) != 0 {
+            return Err(IllegalAction::Occupied)
+        }
+
+        // Check to see if any of the adjacent roads are the player's, an opponent's
+        // building on the shared vertex breaks the connection
+        let topology = self.board.get_topology();
+        for v in topology.get_edge_vertices(edge) {
+            if self.board.is_opponent_building(player, *v) {
+                continue;
+            }
+            for e in topology.get_vertex_edges(*v) {
+                if self.board.get_road_by_id(*e) == player {
+                    return Ok(())
+                }
+            }
+        }
+        Err(IllegalAction::NotConnected)
+    }
+
+    /**
+     * Check if a settlement can be built on a vertex, at the end of one of the player's roads
+     */
+    pub(super) fn check_settlement(&self, player: u8, coord: &VertexCoord) -> Result<(), IllegalAction> {
+        if self.get_pieces(player).settlements == 0 {
+            return Err(IllegalAction::NoPiecesLeft)
+        }
+        self.check_settlement_at(player, self.get_vertex_id(coord)?)
+    }
+
+    /**
+     * Check if a settlement can be built on the vertex with an id, not counting the player's pieces
+     */
+    pub(super) fn check_settlement_at(&self, player: u8, vertex: VertexId) -> Result<(), IllegalAction> {
+        self.check_building_vertex(vertex)?;
+
+        // Make sure the player has a road adjacent to this vertex
+        for e in self.board.get_topology().get_vertex_edges(vertex) {
+            if self.board.get_road_by_id(*e) == player {
+                return Ok(())
+            }
+        }
+        Err(IllegalAction::NotConnected)
+    }
+
+    /**
+     * Check if a city can replace a settlement on a vertex
+     */
+    pub(super) fn check_city(&self, player: u8, coord: &VertexCoord) -> Result<(), IllegalAction> {
+        if self.get_pieces(player).cities == 0 {
+            return Err(IllegalAction::NoPiecesLeft)
+        }
+        self.check_city_at(player, self.get_vertex_id(coord)?)
+    }
+
+    /**
+     * Check if a city can replace a settlement on the vertex with an id, not counting the
+     * player's pieces
+     */
+    pub(super) fn check_city_at(&self, player: u8, vertex: VertexId) -> Result<(), IllegalAction> {
+        if Building::from_u8(self.board.get_building_by_id(vertex)) != (Building::Settlement, player) {
+            return Err(IllegalAction::NotOwnSettlement)
+        }
+        Ok(())
+    }
+
+    /**
+     * Check if a building fits on a vertex without breaking the distance rule
+     */
+    fn check_building_location(&self, coord: &VertexCoord) -> Result<(), IllegalAction> {
+        self.check_building_vertex(self.get_vertex_id(coord)?)
+    }
+
+    fn check_building_vertex(&self, vertex: VertexId) -> Result<(), IllegalAction> {
+        if self.board.get_building_by_id(vertex) & PLAYER_VALUE_MASK != 0 {
+            return Err(IllegalAction::Occupied)
+        }
+        if !self.board.is_valid_building_vertex(vertex) {
+            return Err(IllegalAction::DistanceRule)
+        }
+        Ok(())
+    }
+
+    /**
+     * Check if an edge is on the board and has no road
+     */
+    fn check_edge(&self, coord: &EdgeCoord) -> Result<(), IllegalAction> {
+        if self.board.get_road_by_id(self.get_edge_id(coord)?) != 0 {
+            return Err(IllegalAction::Occupied)
+        }
+        Ok(())
+    }
+
+    /**
+     * Get the id of a vertex on the board
+     */
+    fn get_vertex_id(&self, coord: &VertexCoord) -> Result<VertexId, IllegalAction> {
+        self.board.get_topology().get_vertex_id(coord).ok_or(IllegalAction::InvalidCoordinate)
+    }
+
+    /**
+     * Get the id of an edge on the board
+     */
+    fn get_edge_id(&self, coord: &EdgeCoord) -> Result<EdgeId, IllegalAction> {
+        self.board.get_topology().get_edge_id(coord).ok_or(IllegalAction::InvalidCoordinate)
+    }
+
+    /**
+     * Check if a trade with the bank is at the player's rate and both sides can pay
+     */
+    fn check_port(&self, player: u8, give: u8, from: Resource, receive: u8, to: Resource) -> Result<(), IllegalAction> {
+        if from == to || receive == 0 || give as u16 != receive as u16 * self.board.get_trade_rate(player, from) as u16 {
+            return Err(IllegalAction::InvalidTrade)
+        }
+        if self.get_hand(player)[from] < give as u32 {
+            return Err(IllegalAction::InsufficientResources)
+        }
+        if self.bank.count(to) < receive as u32 {
+            return Err(IllegalAction::BankEmpty)
+        }
+        Ok(())
+    }
+
+    /**
+     * Check if a player can pay for their answer to a trade offer. A counter offer must
+     * also be one the active player can pay for.
+     */
+    fn check_trade_answer(&self, player: u8, offer: &TradeOffer, response: &TradeResponse) -> Result<(), IllegalAction> {
+        match response {
+            TradeResponse::Accept => self.check_cards(player, offer.receive),
+            TradeResponse::Counter(counter) => {
+                if !counter.is_valid() {
+                    return Err(IllegalAction::InvalidTrade)
+                }
+                self.check_cards(player, counter.receive)?;
+                self.check_cards(self.turn_player, counter.give)
+            }
+            TradeResponse::Reject => Ok(()),
+        }
+    }
+
+    fn check_cards(&self, player: u8, cards: Hand) -> Result<(), IllegalAction> {
+        if !self.get_hand(player).has(cards) {
+            return Err(IllegalAction::InsufficientResources)
+        }
+        Ok(())
+    }
+
+    /**
+     * Check if a player can play a development card this turn
+     */
+    pub(super) fn check_dev(&self, player: u8, card: DevCard) -> Result<(), IllegalAction> {
+        let count = self.get_devs(player)[card];
+        if count == 0 {
+            return Err(IllegalAction::NoDevCard)
+        }
+        if self.dev_played {
+            return Err(IllegalAction::DevCardAlreadyPlayed)
+        }
+        if count <= self.bought_devs[card] {
+            return Err(IllegalAction::DevCardBoughtThisTurn)
+        }
+        Ok(())
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{coordinate::{EdgeCoord, VertexCoord}, tile::Resource};
+    use crate::game::{
+        action::{GameAction, PlayerAction, RecordedAction}, bank::Bank, devs::{DevCard, DevHand}, hand::{self, Hand},
+        phase::TurnPhase, Game,
+    };
+
+    use super::IllegalAction;
+
+    // The documented 2 player game, after the placements and the first roll
+    const PLACED: &str = "2/W2O3B10S8DL5O6B3S4W10O11W9B12S6L4B5S9L11W8/WTOTTLBST/\
+        #GS;#FP1;1FP;1S1(0,-2,3);1IRX-3,3;#FP2;2FP;2S1(1,1,-3);2IRX1,-2;\
+        #SP2;2SP;2S2(-2,1,2);2IRY1,-2;#SP1;1SP;1S2(3,-1,-1);1IRZ2,-1;1RO;#RO1,8";
+
+    fn placed_game(hand: Hand) -> Game {
+        let mut game = Game::from_notation(PLACED.to_string());
+        assert_eq!(*game.get_phase(), TurnPhase::Main(1));
+        game.set_hand(1, hand);
+        game
+    }
+
+    /**
+     * Everything an illegal action must leave as it was
+     */
+    fn snapshot(game: &Game) -> String {
+        let mut str = format!(
+            "{} {:?} {:?} {:?} {:?} {}",
+            game.record_as_notation(), game.phase, game.board.roads, game.board.buildings,
+            game.bank.get_resources(), game.undo_steps.len(),
+        );
+        for player in 1 ..= game.player_count {
+            str += &format!(" {} {} {:?}", game.get_hand(player), game.get_devs(player), game.get_pieces(player));
+        }
+        str
+    }
+
+    fn assert_rejected(game: &mut Game, player: u8, action: PlayerAction, err: IllegalAction) {
+        let before = snapshot(game);
+        assert_eq!(game.apply_as(player, action.clone()), Err(err), "{:?}", action);
+        assert_eq!(snapshot(game), before, "{:?}", action);
+    }
+
+    /**
+     * A vertex the distance rule allows a settlement on, away from the player's roads
+     */
+    fn unconnected_vertex(game: &Game, player: u8) -> VertexCoord {
+        let topology = game.board.get_topology();
+        game.board.get_valid_starting_buiding_coords().find(|coord| {
+            let vertex = topology.get_vertex_id(coord).unwrap();
+            topology.get_vertex_edges(vertex).iter().all(|e| game.board.get_road_by_id(*e) != player)
+        }).unwrap()
+    }
+
+    fn all_cards() -> Hand {
+        hand::SETTLE_RECIPE + hand::CITY_RECIPE + hand::DEV_RECIPE
+    }
+
+    #[test]
+    fn building_next_to_a_building_breaks_the_distance_rule() {
+        let mut game = placed_game(all_cards());
+        let settlement = VertexCoord::new(0, -2, 3);
+        let road = EdgeCoord::new(0, -3, 3);
+
+        // The end of the player's road is next to their settlement
+        let topology = game.board.get_topology();
+        let end = topology.get_edge_vertices(topology.get_edge_id(&road).unwrap()).iter()
+            .map(|v| topology.get_vertices()[*v])
+            .find(|coord| *coord != settlement)
+            .unwrap();
+        assert_rejected(&mut game, 1, PlayerAction::PlaceSettlement(end), IllegalAction::DistanceRule);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceSettlement(settlement), IllegalAction::Occupied);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceRoad(road), IllegalAction::Occupied);
+    }
+
+    #[test]
+    fn pieces_must_connect_to_the_players_roads() {
+        let mut game = placed_game(all_cards());
+        let vertex = unconnected_vertex(&game, 1);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceSettlement(vertex), IllegalAction::NotConnected);
+
+        // An edge with no road of the player at either end
+        let topology = game.board.get_topology();
+        let edge = (0 .. topology.get_edges().len()).find(|e| {
+            topology.get_edge_vertices(*e).iter().all(|v| {
+                game.board.get_building_by_id(*v) == 0
+                    && topology.get_vertex_edges(*v).iter().all(|e| game.board.get_road_by_id(*e) == 0)
+            })
+        }).map(|e| topology.get_edges()[e]).unwrap();
+        assert_rejected(&mut game, 1, PlayerAction::PlaceRoad(edge), IllegalAction::NotConnected);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceCity(vertex), IllegalAction::NotOwnSettlement);
+    }
+
+    #[test]
+    fn location_is_checked_before_the_cards() {
+        let mut game = placed_game(Hand::EMPTY);
+        let vertex = unconnected_vertex(&game, 1);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceSettlement(vertex), IllegalAction::NotConnected);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceCity(VertexCoord::new(1, 1, -3)), IllegalAction::NotOwnSettlement);
+    }
+
+    #[test]
+    fn building_needs_the_cards() {
+        let mut game = placed_game(Hand::of(Resource::Wheat, 1));
+        let road = game.get_legal_road_locations(1).next().unwrap();
+        assert_rejected(&mut game, 1, PlayerAction::PlaceRoad(road), IllegalAction::InsufficientResources);
+        assert_rejected(&mut game, 1, PlayerAction::PlaceCity(VertexCoord::new(0, -2, 3)), IllegalAction::InsufficientResources);
+        assert_rejected(&mut game, 1, PlayerAction::BuyDev, IllegalAction::InsufficientResources);
+        let trade = PlayerAction::Port(game.board.get_trade_rate(1, Resource::Wheat), Resource::Wheat, 1, Resource::Ore);
+        assert_rejected(&mut game, 1, trade, IllegalAction::InsufficientResources);
+
+        game.set_hand(1, hand::ROAD_RECIPE);
+        assert_eq!(game.apply(PlayerAction::PlaceRoad(road)), Ok(()));
+        assert!(game.get_hand(1).is_empty());
+    }
+
+    #[test]
+    fn actions_are_played_in_their_phase_by_the_waiting_player() {
+        let mut game = placed_game(all_cards());
+        assert_rejected(&mut game, 1, PlayerAction::Roll, IllegalAction::WrongPhase);
+        let vertex = unconnected_vertex(&game, 1);
+        assert_rejected(&mut game, 1, PlayerAction::FirstSettlement(vertex), IllegalAction::WrongPhase);
+        assert_rejected(&mut game, 1, PlayerAction::Discard(Hand::of(Resource::Wheat, 1)), IllegalAction::WrongPhase);
+        assert_rejected(&mut game, 2, PlayerAction::Pass, IllegalAction::WrongPlayer);
+        assert_rejected(&mut game, 3, PlayerAction::Pass, IllegalAction::WrongPlayer);
+
+        game.apply(PlayerAction::Pass).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::PreRoll(2));
+        let road = game.get_legal_road_locations(2).next().unwrap();
+        game.set_hand(2, hand::ROAD_RECIPE);
+        assert_rejected(&mut game, 2, PlayerAction::PlaceRoad(road), IllegalAction::WrongPhase);
+        assert_rejected(&mut game, 1, PlayerAction::Roll, IllegalAction::WrongPlayer);
+    }
+
+    #[test]
+    fn one_development_card_a_turn() {
+        let mut game = placed_game(Hand::EMPTY);
+        game.set_devs(1, DevHand::of(DevCard::Knight, 1) + DevHand::of(DevCard::Monopoly, 1));
+        game.apply(PlayerAction::PlayMono).unwrap();
+        game.apply(PlayerAction::Monopoly(Resource::Ore)).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::Main(1));
+        assert_rejected(&mut game, 1, PlayerAction::PlayKnight, IllegalAction::DevCardAlreadyPlayed);
+        assert!(!game.legal_actions().contains(&PlayerAction::PlayKnight));
+
+        // The next player may play a card before rolling
+        game.apply(PlayerAction::Pass).unwrap();
+        game.set_devs(2, DevHand::of(DevCard::Knight, 1));
+        assert!(game.legal_actions().contains(&PlayerAction::PlayKnight));
+        game.apply(PlayerAction::PlayKnight).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::RobberMove(2));
+    }
+
+    #[test]
+    fn bought_development_card_waits_for_a_later_turn() {
+        let mut game = placed_game(hand::DEV_RECIPE + hand::DEV_RECIPE);
+        game.bank = Bank::new(19, vec![DevCard::Knight, DevCard::Knight]);
+
+        game.apply(PlayerAction::BuyDev).unwrap();
+        assert_eq!(game.get_devs(1), DevHand::of(DevCard::Knight, 1));
+        assert_rejected(&mut game, 1, PlayerAction::PlayKnight, IllegalAction::DevCardBoughtThisTurn);
+        assert!(!game.legal_actions().contains(&PlayerAction::PlayKnight));
+
+        // A second knight bought this turn does not make the first one playable
+        game.apply(PlayerAction::BuyDev).unwrap();
+        assert_rejected(&mut game, 1, PlayerAction::PlayKnight, IllegalAction::DevCardBoughtThisTurn);
+
+        // Both can be played on the player's next turn, one at a time
+        game.apply(PlayerAction::Pass).unwrap();
+        game.apply_recorded_action(&RecordedAction::Player(2, PlayerAction::Roll));
+        game.apply_recorded_action(&RecordedAction::Game(GameAction::Roll(2, 8)));
+        game.apply(PlayerAction::Pass).unwrap();
+        assert_eq!(*game.get_phase(), TurnPhase::PreRoll(1));
+        game.apply(PlayerAction::PlayKnight).unwrap();
+        assert_eq!(game.get_devs(1), DevHand::of(DevCard::Knight, 1));
+        assert_eq!(game.get_army_size(1), 1);
+    }
+
+    #[test]
+    fn development_card_not_bought_this_turn_can_be_played() {
+        let mut game = placed_game(hand::DEV_RECIPE);
+        game.bank = Bank::new(19, vec![DevCard::Knight]);
+        game.set_devs(1, DevHand::of(DevCard::Knight, 1));
+
+        game.apply(PlayerAction::BuyDev).unwrap();
+        assert_eq!(game.get_devs(1), DevHand::of(DevCard::Knight, 2));
+        game.apply(PlayerAction::PlayKnight).unwrap();
+        assert_eq!(game.get_devs(1), DevHand::of(DevCard::Knight, 1));
+    }
+
+}
@@ -0,0 +1,35 @@
+/*!
+ * A Catan game engine. A `Game` is played either by its agents with `Game::start`, or
+ * one decision at a time with `Game::legal_actions` and `Game::apply`. Games and boards
+ * can be written to and read from the notation described in `notation.md`.
+ */
+
+mod board;
+mod game;
+
+pub use board::{
+    building::Building,
+    coordinate::{Coordinate2, EdgeCoord, ParseCoordinateError, VertexCoord},
+    generate::PortPlacement,
+    port::Port,
+    tile::{Resource, Tile},
+    topology::{EdgeId, TileId, Topology, VertexId},
+    Board,
+    Layout,
+    STANDARD_RADIUS,
+};
+pub use game::{
+    action::{GameAction, PlayerAction, RecordedAction},
+    agent::{PlayerAgent, RandomAgent},
+    bank::Bank,
+    devs::{DevCard, DevHand, DevHandError},
+    hand::{Hand, HandError},
+    phase::TurnPhase,
+    pieces::Pieces,
+    trade::{TradeOffer, TradeResponse},
+    validate::IllegalAction,
+    Game,
+    GameResult,
+    DEFAULT_VP_TARGET,
+    MAX_PLAYERS,
+};
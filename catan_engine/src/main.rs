@@ -1,12 +1,28 @@
-use std::io::stdin;
+use std::env;
 
-use board::{coordinate::Coordinate3, Board};
-use game::{hand, Game};
-mod board;
-mod game;
+use catan_engine::Game;
 
+/**
+ * Play a game between random agents: `catan_engine [players] [seed]`
+ */
 fn main() {
-    let mut game = Game::random(2);
-    game.start();
+    let mut args = env::args().skip(1);
+    let player_count = match args.next() {
+        Some(arg) => arg.parse().unwrap_or_else(|_| panic!("Invalid player count: {}", arg)),
+        None => 2,
+    };
+    let mut game = match args.next() {
+        Some(seed) => Game::random_from_seed(player_count, seed),
+        None => Game::random(player_count),
+    };
+    println!("Seed: {}", game.get_seed());
+    let result = game.start();
+
+    // Log every move, then show the final position
+    for action in game.get_record() {
+        println!("{}", action.as_notation());
+    }
     game.print();
-}
\ No newline at end of file
+    println!("Game over");
+    println!("{}", result);
+}